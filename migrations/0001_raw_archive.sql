@@ -0,0 +1,14 @@
+-- APIレスポンスを加工せずに保存するアーカイブ
+CREATE SCHEMA IF NOT EXISTS raw;
+
+CREATE TABLE IF NOT EXISTS raw.responses (
+    id         BIGSERIAL PRIMARY KEY,
+    api        TEXT        NOT NULL,              -- 取得元のベースURL
+    endpoint   TEXT        NOT NULL,              -- パステンプレート（例: /coins/{id}）
+    params     JSONB       NOT NULL DEFAULT '{}', -- パス・クエリパラメータ
+    body       JSONB       NOT NULL,              -- レスポンス本体
+    fetched_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS responses_endpoint_fetched_at_idx
+    ON raw.responses (endpoint, fetched_at);
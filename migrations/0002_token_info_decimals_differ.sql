@@ -0,0 +1,3 @@
+-- 同じトークンでチェーンごとに decimals が異なる場合に立てるフラグ
+ALTER TABLE contract.token_info
+    ADD COLUMN IF NOT EXISTS decimals_differ BOOLEAN;
@@ -0,0 +1,35 @@
+-- /coins/{id} の market_data を通貨ごとに保存（coins.detail とは id, fetched_at で対応）
+CREATE TABLE IF NOT EXISTS coins.market_snapshot (
+    coin_id                                   TEXT        NOT NULL,
+    vs_currency                               TEXT        NOT NULL,
+    current_price                             NUMERIC,
+    market_cap                                NUMERIC,
+    fully_diluted_valuation                   NUMERIC,
+    total_volume                              NUMERIC,
+    high_24h                                  NUMERIC,
+    low_24h                                   NUMERIC,
+    ath                                       NUMERIC,
+    ath_change_percentage                     NUMERIC,
+    ath_date                                  TIMESTAMPTZ,
+    atl                                       NUMERIC,
+    atl_change_percentage                     NUMERIC,
+    atl_date                                  TIMESTAMPTZ,
+    price_change_24h                          NUMERIC,
+    price_change_percentage_1h                NUMERIC,
+    price_change_percentage_24h               NUMERIC,
+    price_change_percentage_7d                NUMERIC,
+    price_change_percentage_14d               NUMERIC,
+    price_change_percentage_30d               NUMERIC,
+    price_change_percentage_60d               NUMERIC,
+    price_change_percentage_200d              NUMERIC,
+    price_change_percentage_1y                NUMERIC,
+    market_cap_change_24h                     NUMERIC,
+    market_cap_change_percentage_24h          NUMERIC,
+    -- 供給量は通貨に依らないため各通貨の行に同じ値が入る
+    circulating_supply                        NUMERIC,
+    total_supply                              NUMERIC,
+    max_supply                                NUMERIC,
+    last_updated                              TIMESTAMPTZ,
+    fetched_at                                TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (coin_id, vs_currency, fetched_at)
+);
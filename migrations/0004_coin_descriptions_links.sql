@@ -0,0 +1,26 @@
+-- /coins/{id} の description を言語コードごとに保存（HTMLはサニタイズ済み）
+CREATE TABLE IF NOT EXISTS coins.descriptions (
+    coin_id     TEXT        NOT NULL,
+    language    TEXT        NOT NULL,
+    description TEXT        NOT NULL,
+    fetched_at  TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (coin_id, language, fetched_at)
+);
+
+-- /coins/{id} の links（空文字は除外して保存）
+CREATE TABLE IF NOT EXISTS coins.links (
+    coin_id             TEXT        NOT NULL,
+    homepage            TEXT[],
+    blockchain_site     TEXT[],
+    official_forum_url  TEXT[],
+    chat_url            TEXT[],
+    announcement_url    TEXT[],
+    twitter_screen_name TEXT,
+    facebook_username   TEXT,
+    telegram_channel_identifier TEXT,
+    subreddit_url       TEXT,
+    repos_github        TEXT[],
+    repos_bitbucket     TEXT[],
+    fetched_at          TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (coin_id, fetched_at)
+);
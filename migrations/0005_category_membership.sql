@@ -0,0 +1,51 @@
+-- /coins/categories/list の参照テーブル（カテゴリIDと名前）
+CREATE TABLE IF NOT EXISTS categories.category_list (
+    category_id TEXT        PRIMARY KEY,
+    name        TEXT        NOT NULL,
+    fetched_at  TIMESTAMPTZ NOT NULL
+);
+
+-- /coins/{id} の categories（カテゴリ名）を取得ごとに保存
+-- category_id は挿入時点の category_list から名前で引いたもの
+CREATE TABLE IF NOT EXISTS categories.coin_membership (
+    coin_id       TEXT        NOT NULL,
+    category_name TEXT        NOT NULL,
+    category_id   TEXT,
+    fetched_at    TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (coin_id, category_name, fetched_at)
+);
+
+CREATE INDEX IF NOT EXISTS coin_membership_category_idx
+    ON categories.coin_membership (category_id, fetched_at);
+
+-- コインがカテゴリに入った／外れたタイミング
+-- coins.detail の前回の取得と比べて差分があった取得時刻を event とともに返す
+CREATE OR REPLACE VIEW categories.coin_membership_changes AS
+WITH fetches AS (
+    SELECT
+        id AS coin_id,
+        fetched_at,
+        lag(fetched_at) OVER (PARTITION BY id ORDER BY fetched_at) AS prev_fetched_at
+    FROM coins.detail
+)
+SELECT f.coin_id, cur.category_name, cur.category_id, 'entered' AS event, f.fetched_at
+FROM fetches f
+JOIN categories.coin_membership cur
+  ON cur.coin_id = f.coin_id AND cur.fetched_at = f.fetched_at
+WHERE NOT EXISTS (
+    SELECT 1 FROM categories.coin_membership prev
+    WHERE prev.coin_id = f.coin_id
+      AND prev.fetched_at = f.prev_fetched_at
+      AND prev.category_name = cur.category_name
+)
+UNION ALL
+SELECT f.coin_id, prev.category_name, prev.category_id, 'left' AS event, f.fetched_at
+FROM fetches f
+JOIN categories.coin_membership prev
+  ON prev.coin_id = f.coin_id AND prev.fetched_at = f.prev_fetched_at
+WHERE NOT EXISTS (
+    SELECT 1 FROM categories.coin_membership cur
+    WHERE cur.coin_id = f.coin_id
+      AND cur.fetched_at = f.fetched_at
+      AND cur.category_name = prev.category_name
+);
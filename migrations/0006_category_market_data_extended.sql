@@ -0,0 +1,7 @@
+-- /coins/categories の追加項目
+ALTER TABLE categories.category_market_data
+    ADD COLUMN IF NOT EXISTS market_cap_change_24h NUMERIC,
+    ADD COLUMN IF NOT EXISTS top_3_coin_ids        TEXT[],
+    ADD COLUMN IF NOT EXISTS top_3_coins           TEXT[],  -- 画像URL（APIの値そのまま）
+    ADD COLUMN IF NOT EXISTS content               TEXT,
+    ADD COLUMN IF NOT EXISTS updated_at            TIMESTAMPTZ;
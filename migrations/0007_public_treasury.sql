@@ -0,0 +1,42 @@
+-- /companies/public_treasury/{coin_id} の企業ごとの追加項目
+ALTER TABLE companies.public_holdings
+    ADD COLUMN IF NOT EXISTS coin_id               TEXT,
+    ADD COLUMN IF NOT EXISTS company_key           TEXT,  -- 銘柄コード（無ければ企業名）から作る安定キー
+    ADD COLUMN IF NOT EXISTS country               TEXT,
+    ADD COLUMN IF NOT EXISTS total_entry_value_usd NUMERIC;
+
+-- 既存行はBTCのみ取得していた（company_key は companies.rs と同じ規則で埋める）
+UPDATE companies.public_holdings
+SET coin_id = 'bitcoin',
+    company_key = CASE
+        WHEN coalesce(trim(symbol), '') <> '' THEN upper(trim(symbol))
+        ELSE 'name:' || lower(trim(company_name))
+    END
+WHERE coin_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS public_holdings_company_idx
+    ON companies.public_holdings (company_key, coin_id, fetched_at);
+
+-- レスポンス全体の合計値
+CREATE TABLE IF NOT EXISTS companies.treasury_totals (
+    coin_id              TEXT        NOT NULL,
+    total_holdings       NUMERIC,
+    total_value_usd      NUMERIC,
+    market_cap_dominance NUMERIC,
+    fetched_at           TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (coin_id, fetched_at)
+);
+
+-- 企業ごとの保有量の推移（前回取得からの増減）
+CREATE OR REPLACE VIEW companies.holdings_changes AS
+SELECT
+    company_key,
+    coin_id,
+    company_name,
+    total_holdings,
+    total_holdings - lag(total_holdings) OVER w AS holdings_change,
+    total_value_usd,
+    fetched_at
+FROM companies.public_holdings
+WHERE company_key IS NOT NULL
+WINDOW w AS (PARTITION BY company_key, coin_id ORDER BY fetched_at);
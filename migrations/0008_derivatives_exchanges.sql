@@ -0,0 +1,51 @@
+-- /derivatives の追加項目
+ALTER TABLE derivatives.derivative_markets
+    ADD COLUMN IF NOT EXISTS market                      TEXT,
+    ADD COLUMN IF NOT EXISTS price_percentage_change_24h NUMERIC,
+    ADD COLUMN IF NOT EXISTS index_price                 NUMERIC,
+    ADD COLUMN IF NOT EXISTS basis                       NUMERIC,
+    ADD COLUMN IF NOT EXISTS spread                      NUMERIC,
+    ADD COLUMN IF NOT EXISTS funding_rate                NUMERIC,
+    ADD COLUMN IF NOT EXISTS open_interest               NUMERIC,
+    ADD COLUMN IF NOT EXISTS volume_24h                  NUMERIC,
+    ADD COLUMN IF NOT EXISTS last_traded_at              TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS expired_at                  TIMESTAMPTZ;
+
+-- /derivatives/exchanges（取引所ごとの建玉・出来高）
+CREATE TABLE IF NOT EXISTS derivatives.exchanges (
+    id                        TEXT        NOT NULL,
+    name                      TEXT,
+    open_interest_btc         NUMERIC,
+    trade_volume_24h_btc      NUMERIC,
+    number_of_perpetual_pairs INTEGER,
+    number_of_futures_pairs   INTEGER,
+    year_established          INTEGER,
+    country                   TEXT,
+    url                       TEXT,
+    fetched_at                TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (id, fetched_at)
+);
+
+-- /derivatives/exchanges/{id}?include_tickers=all のティッカー
+CREATE TABLE IF NOT EXISTS derivatives.exchange_tickers (
+    exchange_id       TEXT        NOT NULL,
+    symbol            TEXT        NOT NULL,
+    base              TEXT,
+    target            TEXT,
+    contract_type     TEXT,
+    last              NUMERIC,
+    price_change_24h  NUMERIC,
+    index_price       NUMERIC,
+    basis             NUMERIC,
+    spread            NUMERIC,
+    funding_rate      NUMERIC,
+    open_interest_usd NUMERIC,
+    volume_24h        NUMERIC,
+    volume_24h_usd    NUMERIC,
+    last_traded_at    TIMESTAMPTZ,
+    expired_at        TIMESTAMPTZ,
+    fetched_at        TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS exchange_tickers_exchange_idx
+    ON derivatives.exchange_tickers (exchange_id, fetched_at);
@@ -0,0 +1,22 @@
+-- /derivatives には id が無いため market + symbol + contract_type を自然キーにする
+-- 旧来の id 列は書き込まなくなるので、id の主キーと NOT NULL を外す
+-- （主キーの列は NOT NULL を外せないため、主キーを先に削除する）
+DO $$
+DECLARE
+    pkey TEXT;
+BEGIN
+    SELECT conname INTO pkey
+    FROM pg_constraint
+    WHERE conrelid = 'derivatives.derivative_markets'::regclass
+      AND contype = 'p';
+
+    IF pkey IS NOT NULL THEN
+        EXECUTE format('ALTER TABLE derivatives.derivative_markets DROP CONSTRAINT %I', pkey);
+    END IF;
+END
+$$;
+
+ALTER TABLE derivatives.derivative_markets ALTER COLUMN id DROP NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS derivative_markets_key_idx
+    ON derivatives.derivative_markets (market, symbol, (coalesce(contract_type, '')), fetched_at);
@@ -0,0 +1,21 @@
+-- 無期限先物の資金調達率の時系列（/derivatives の contract_type = 'perpetual'）
+-- funding_rate は1回あたりの%、annualized_funding_rate は8時間ごと（年1095回）として年率換算
+CREATE TABLE IF NOT EXISTS derivatives.funding_rates (
+    market                  TEXT        NOT NULL,
+    symbol                  TEXT        NOT NULL,
+    funding_rate            NUMERIC     NOT NULL,
+    annualized_funding_rate NUMERIC     NOT NULL,
+    basis                   NUMERIC,
+    price                   NUMERIC,
+    index_price             NUMERIC,
+    open_interest           NUMERIC,
+    fetched_at              TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (market, symbol, fetched_at)
+);
+
+CREATE INDEX IF NOT EXISTS funding_rates_fetched_at_idx
+    ON derivatives.funding_rates (fetched_at);
+
+-- 期日付き先物のベーシスを満期までの日数で年率換算した値
+ALTER TABLE derivatives.derivative_markets
+    ADD COLUMN IF NOT EXISTS annualized_basis NUMERIC;
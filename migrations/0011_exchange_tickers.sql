@@ -0,0 +1,24 @@
+-- /exchanges/{id}/tickers の取引ペア（どのコインがどこで、どのスプレッドで取引されているか）
+CREATE TABLE IF NOT EXISTS exchanges.tickers (
+    exchange_id               TEXT        NOT NULL,
+    base                      TEXT        NOT NULL,
+    target                    TEXT        NOT NULL,
+    coin_id                   TEXT,
+    target_coin_id            TEXT,
+    last                      NUMERIC,
+    volume                    NUMERIC,
+    converted_last_usd        NUMERIC,
+    converted_volume_usd      NUMERIC,
+    bid_ask_spread_percentage NUMERIC,
+    trust_score               TEXT,
+    is_stale                  BOOLEAN,
+    is_anomaly                BOOLEAN,
+    last_traded_at            TIMESTAMPTZ,
+    fetched_at                TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS tickers_exchange_idx
+    ON exchanges.tickers (exchange_id, fetched_at);
+
+CREATE INDEX IF NOT EXISTS tickers_coin_idx
+    ON exchanges.tickers (coin_id, fetched_at);
@@ -0,0 +1,36 @@
+-- /exchanges の追加項目
+ALTER TABLE exchanges.exchange_info
+    ADD COLUMN IF NOT EXISTS url                             TEXT,
+    ADD COLUMN IF NOT EXISTS image                           TEXT,
+    ADD COLUMN IF NOT EXISTS description                     TEXT,
+    ADD COLUMN IF NOT EXISTS has_trading_incentive           BOOLEAN,
+    ADD COLUMN IF NOT EXISTS trust_score_rank                INT,
+    ADD COLUMN IF NOT EXISTS trade_volume_24h_btc_normalized NUMERIC;
+
+-- 全ページを取得し終えた一覧の取得（ページごとに fetched_at が異なるため期間で束ねる）
+-- 途中で失敗した取得は記録しないので、消失の誤検知にはならない
+CREATE TABLE IF NOT EXISTS exchanges.list_runs (
+    started_at     TIMESTAMPTZ NOT NULL PRIMARY KEY,
+    finished_at    TIMESTAMPTZ NOT NULL,
+    exchange_count INT         NOT NULL
+);
+
+-- 前回の一覧にあって今回の一覧に無い取引所
+CREATE OR REPLACE VIEW exchanges.disappeared_exchanges AS
+WITH runs AS (
+    SELECT
+        started_at,
+        finished_at,
+        lag(started_at) OVER (ORDER BY started_at) AS prev_started_at,
+        lag(finished_at) OVER (ORDER BY started_at) AS prev_finished_at
+    FROM exchanges.list_runs
+)
+SELECT DISTINCT prev.id AS exchange_id, prev.name, r.started_at AS missing_since
+FROM runs r
+JOIN exchanges.exchange_info prev
+  ON prev.fetched_at BETWEEN r.prev_started_at AND r.prev_finished_at
+WHERE NOT EXISTS (
+    SELECT 1 FROM exchanges.exchange_info cur
+    WHERE cur.id = prev.id
+      AND cur.fetched_at BETWEEN r.started_at AND r.finished_at
+);
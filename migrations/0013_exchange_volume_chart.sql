@@ -0,0 +1,18 @@
+-- /exchanges/{id}/volume_chart(/range) の出来高（BTC建て）
+-- 同じ時刻の点は新しい取得で上書きする（バックフィルを何度実行しても重複しない）
+CREATE TABLE IF NOT EXISTS exchanges.volume_chart (
+    exchange_id TEXT        NOT NULL,
+    timestamp   TIMESTAMPTZ NOT NULL,
+    volume_btc  NUMERIC,
+    fetched_at  TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (exchange_id, timestamp)
+);
+
+-- 取引所ごとの日次出来高（UTCの日ごとに最後の点）
+CREATE OR REPLACE VIEW exchanges.daily_volume AS
+SELECT DISTINCT ON (exchange_id, (timestamp AT TIME ZONE 'UTC')::date)
+    exchange_id,
+    (timestamp AT TIME ZONE 'UTC')::date AS day,
+    volume_btc
+FROM exchanges.volume_chart
+ORDER BY exchange_id, (timestamp AT TIME ZONE 'UTC')::date, timestamp DESC;
@@ -0,0 +1,11 @@
+-- /global/decentralized_finance_defi の時系列（数値は文字列で返るため NUMERIC でそのまま保持）
+CREATE TABLE IF NOT EXISTS global.defi_stats (
+    defi_market_cap         NUMERIC,
+    eth_market_cap          NUMERIC,
+    defi_to_eth_ratio       NUMERIC,
+    trading_volume_24h      NUMERIC,
+    defi_dominance          NUMERIC,
+    top_coin_name           TEXT,
+    top_coin_defi_dominance NUMERIC,
+    fetched_at              TIMESTAMPTZ NOT NULL PRIMARY KEY
+);
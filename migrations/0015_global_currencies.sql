@@ -0,0 +1,21 @@
+-- /global の追加項目
+ALTER TABLE global.market_stats
+    ADD COLUMN IF NOT EXISTS market_cap_change_percentage_24h_usd NUMERIC,
+    ADD COLUMN IF NOT EXISTS updated_at                           TIMESTAMPTZ;
+
+-- 通貨ごとの時価総額・出来高（total_market_cap / total_volume の全通貨）
+CREATE TABLE IF NOT EXISTS global.currency_totals (
+    vs_currency      TEXT        NOT NULL,
+    total_market_cap NUMERIC,
+    total_volume     NUMERIC,
+    fetched_at       TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (vs_currency, fetched_at)
+);
+
+-- コインごとの市場占有率（market_cap_percentage の全コイン）
+CREATE TABLE IF NOT EXISTS global.dominance (
+    coin                  TEXT        NOT NULL,
+    market_cap_percentage NUMERIC,
+    fetched_at            TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (coin, fetched_at)
+);
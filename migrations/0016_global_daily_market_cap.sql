@@ -0,0 +1,11 @@
+-- 全体の時価総額・出来高の日次系列（UTCの日ごとに最後の値）
+-- source は 'chart'（/global/market_cap_chart）または 'snapshot'（保存済みの /global から再構成）
+CREATE TABLE IF NOT EXISTS global.daily_market_cap (
+    vs_currency      TEXT        NOT NULL,
+    day              DATE        NOT NULL,
+    total_market_cap NUMERIC,
+    total_volume     NUMERIC,
+    source           TEXT        NOT NULL,
+    fetched_at       TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (vs_currency, day)
+);
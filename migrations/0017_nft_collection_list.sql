@@ -0,0 +1,19 @@
+-- /nfts/list の全コレクション（より新しい取得でのみ更新）
+CREATE TABLE IF NOT EXISTS nfts.collection_list (
+    id                TEXT        NOT NULL PRIMARY KEY,
+    contract_address  TEXT,
+    name              TEXT,
+    asset_platform_id TEXT,
+    symbol            TEXT,
+    fetched_at        TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS collection_list_platform_idx
+    ON nfts.collection_list (asset_platform_id, contract_address);
+
+-- 全コレクションを少しずつ巡回するための位置（最後に詳細を取得したID）
+CREATE TABLE IF NOT EXISTS nfts.refresh_cursor (
+    name       TEXT        NOT NULL PRIMARY KEY,
+    last_id    TEXT,
+    updated_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,17 @@
+-- /nfts/{id} の追加項目（floor_price・volume_24h はUSD、*_native はコレクションのネイティブ通貨建て）
+ALTER TABLE nfts.collections
+    ADD COLUMN IF NOT EXISTS contract_address              TEXT,
+    ADD COLUMN IF NOT EXISTS asset_platform_id             TEXT,
+    ADD COLUMN IF NOT EXISTS native_currency               TEXT,
+    ADD COLUMN IF NOT EXISTS floor_price_native            NUMERIC,
+    ADD COLUMN IF NOT EXISTS volume_24h_native             NUMERIC,
+    ADD COLUMN IF NOT EXISTS market_cap_usd                NUMERIC,
+    ADD COLUMN IF NOT EXISTS market_cap_native             NUMERIC,
+    ADD COLUMN IF NOT EXISTS number_of_unique_addresses    BIGINT,
+    ADD COLUMN IF NOT EXISTS total_supply                  NUMERIC,
+    ADD COLUMN IF NOT EXISTS floor_price_change_24h_usd    NUMERIC,
+    ADD COLUMN IF NOT EXISTS floor_price_change_24h_native NUMERIC,
+    ADD COLUMN IF NOT EXISTS floor_price_change_7d_usd     NUMERIC,
+    ADD COLUMN IF NOT EXISTS floor_price_change_7d_native  NUMERIC,
+    ADD COLUMN IF NOT EXISTS floor_price_change_30d_usd    NUMERIC,
+    ADD COLUMN IF NOT EXISTS floor_price_change_30d_native NUMERIC;
@@ -0,0 +1,14 @@
+-- /nfts/{id}/market_chart の時系列（USD建てとネイティブ通貨建て）
+-- 同じ時刻の点は新しい取得で上書きする（バックフィルを何度実行しても重複しない）
+CREATE TABLE IF NOT EXISTS nfts.market_chart (
+    collection_id      TEXT        NOT NULL,
+    timestamp          TIMESTAMPTZ NOT NULL,
+    floor_price_usd    NUMERIC,
+    floor_price_native NUMERIC,
+    volume_24h_usd     NUMERIC,
+    volume_24h_native  NUMERIC,
+    market_cap_usd     NUMERIC,
+    market_cap_native  NUMERIC,
+    fetched_at         TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (collection_id, timestamp)
+);
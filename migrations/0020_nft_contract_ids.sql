@@ -0,0 +1,12 @@
+-- (asset_platform_id, contract_address) → CoinGecko のコレクションID の対応表
+-- /nfts/{asset_platform_id}/contract/{contract_address} で解決した結果をキャッシュする
+CREATE TABLE IF NOT EXISTS nfts.contract_ids (
+    asset_platform_id TEXT        NOT NULL,
+    contract_address  TEXT        NOT NULL,
+    collection_id     TEXT        NOT NULL,
+    fetched_at        TIMESTAMPTZ NOT NULL
+);
+
+-- EVMのアドレスは大文字小文字を区別しないため、小文字で一意にする
+CREATE UNIQUE INDEX IF NOT EXISTS contract_ids_key_idx
+    ON nfts.contract_ids (asset_platform_id, lower(contract_address));
@@ -0,0 +1,36 @@
+-- 価格を追跡するDEXのペア（チェーン + ペアアドレス）
+CREATE TABLE IF NOT EXISTS onchain.watchlist (
+    chain        TEXT        NOT NULL,
+    pair_address TEXT        NOT NULL,
+    label        TEXT,
+    active       BOOLEAN     NOT NULL DEFAULT true,
+    added_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (chain, pair_address)
+);
+
+-- これまで固定で追跡していた Uniswap v3 USDC/WETH プール
+INSERT INTO onchain.watchlist (chain, pair_address, label)
+VALUES ('ethereum', '0x8ad599c3a0ff1de082011efddc58f1908eb6e6d8', 'Uniswap v3 USDC/WETH 0.3%')
+ON CONFLICT DO NOTHING;
+
+-- ペアの追加項目
+ALTER TABLE onchain.dex_token_prices
+    ADD COLUMN IF NOT EXISTS chain               TEXT,
+    ADD COLUMN IF NOT EXISTS pair_address        TEXT,
+    ADD COLUMN IF NOT EXISTS base_token_symbol   TEXT,
+    ADD COLUMN IF NOT EXISTS quote_token_address TEXT,
+    ADD COLUMN IF NOT EXISTS quote_token_symbol  TEXT,
+    ADD COLUMN IF NOT EXISTS price_native        NUMERIC;
+
+-- 期間（m5 / h1 / h6 / h24）ごとの出来高・取引回数・価格変化率
+CREATE TABLE IF NOT EXISTS onchain.pair_activity (
+    chain        TEXT        NOT NULL,
+    pair_address TEXT        NOT NULL,
+    window_name  TEXT        NOT NULL,
+    volume_usd   NUMERIC,
+    buys         BIGINT,
+    sells        BIGINT,
+    price_change NUMERIC,
+    fetched_at   TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (chain, pair_address, window_name, fetched_at)
+);
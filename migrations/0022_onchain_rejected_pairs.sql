@@ -0,0 +1,18 @@
+-- 取り込めなかったペア（識別情報が欠けている・レスポンスに含まれないなど）とその理由
+CREATE TABLE IF NOT EXISTS onchain.rejected_pairs (
+    chain        TEXT,
+    pair_address TEXT,
+    reason       TEXT        NOT NULL,
+    payload      JSONB,
+    fetched_at   TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS rejected_pairs_fetched_idx
+    ON onchain.rejected_pairs (fetched_at);
+
+-- 欠損時に入れていた仮の値をNULLに戻す
+-- （liquidity.usd は数値で返るのに文字列として読んでいたため、これまでの値はすべて0）
+UPDATE onchain.dex_token_prices SET exchange = NULL WHERE exchange = 'unknown';
+UPDATE onchain.dex_token_prices SET token_address = NULL WHERE token_address = 'unknown';
+UPDATE onchain.dex_token_prices SET price = NULL WHERE price = 0;
+UPDATE onchain.dex_token_prices SET liquidity_usd = NULL WHERE liquidity_usd = 0;
@@ -0,0 +1,13 @@
+-- 資金調達の間隔は取引所・銘柄ごとに異なる（1時間・4時間・8時間など）ため、
+-- 年率は保存せず、参照時にこの表の間隔で換算する
+ALTER TABLE derivatives.funding_rates
+    DROP COLUMN IF EXISTS annualized_funding_rate;
+
+-- 資金調達の間隔（時間）。symbol が空文字の行はその取引所の既定値
+CREATE TABLE IF NOT EXISTS derivatives.funding_intervals (
+    market         TEXT        NOT NULL,
+    symbol         TEXT        NOT NULL DEFAULT '',
+    interval_hours INT         NOT NULL CHECK (interval_hours > 0),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (market, symbol)
+);
@@ -0,0 +1,98 @@
+// 生のAPIレスポンスを raw.responses に保存する
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::Error;
+
+// 1回のAPI取得結果（アーカイブ1行に対応）
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub api: String,
+    pub endpoint: String,
+    pub params: Value,
+    pub body: Value,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl Snapshot {
+    // レスポンス本体を任意の構造体にデシリアライズ
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        T::deserialize(&self.body)
+    }
+
+    // パラメータの値を文字列で取得
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).and_then(Value::as_str)
+    }
+}
+
+// レスポンス本体をそのままJSONBで保存
+pub async fn store(pool: &PgPool, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO raw.responses (
+            api,
+            endpoint,
+            params,
+            body,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        snapshot.api,
+        snapshot.endpoint,
+        snapshot.params,
+        snapshot.body,
+        snapshot.fetched_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 期間内のアーカイブを id 順に読み出す（after_id より後ろを最大 limit 件）
+pub async fn load_page(
+    pool: &PgPool,
+    endpoint: Option<&str>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    after_id: i64,
+    limit: i64,
+) -> Result<Vec<(i64, Snapshot)>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, api, endpoint, params, body, fetched_at
+        FROM raw.responses
+        WHERE ($1::text IS NULL OR endpoint = $1)
+          AND fetched_at >= $2
+          AND fetched_at < $3
+          AND id > $4
+        ORDER BY id
+        LIMIT $5
+        "#,
+        endpoint,
+        from,
+        to,
+        after_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let snapshot = Snapshot {
+                api: row.api,
+                endpoint: row.endpoint,
+                params: row.params,
+                body: row.body,
+                fetched_at: row.fetched_at,
+            };
+            (row.id, snapshot)
+        })
+        .collect())
+}
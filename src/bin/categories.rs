@@ -2,24 +2,13 @@
 use dotenv::dotenv;
 // 環境変数（DB接続情報）を取得するための標準ライブラリ
 use std::env;
-// PostgreSQL用接続プールとBigDecimal型を含むsqlxクレート
-use sqlx::{PgPool, types::BigDecimal};
-// f64 → BigDecimal への変換に使うクレート
-use num_traits::FromPrimitive;
-// JSONをRust構造体に変換するためのデリバイブ用クレート
-use serde::Deserialize;
-
-// APIレスポンスのカテゴリーデータを受け取るための構造体
-#[derive(Debug, Deserialize)]
-struct Category {
-    id: Option<String>,           // カテゴリーID（API上のID）
-    name: Option<String>,         // カテゴリー名
-    market_cap: Option<f64>,      // 時価総額（USD）
-    volume_24h: Option<f64>,      // 24時間の取引量（USD）
-}
+// PostgreSQL用接続プール
+use sqlx::PgPool;
+// API取得と生レスポンスのアーカイブ用
+use coingecko::{categories, client::Client, Error};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Error> {
     // .envファイルを読み込み
     dotenv().ok();
 
@@ -29,50 +18,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // PostgreSQLに非同期接続
     let pool = PgPool::connect(&database_url).await?;
 
-    // CoinGeckoのカテゴリーデータAPIエンドポイント
-    let url = "https://api.coingecko.com/api/v3/coins/categories";
-
-    // APIからデータ取得してVec<Category>型にデシリアライズ
-    let response = reqwest::get(url).await?.json::<Vec<Category>>().await?;
+    // 並び順（--order market_cap_desc など、省略時はAPIの既定）
+    // API呼び出しの前に検証する
+    let mut order = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--order" => order = Some(args.next().ok_or("missing value for --order")?),
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+    if let Some(order) = &order
+        && !categories::ORDERS.contains(&order.as_str())
+    {
+        return Err(format!("--order must be one of {}", categories::ORDERS.join(", ")).into());
+    }
 
-    // 各カテゴリデータを1件ずつ処理
-    for category in response {
-        // レスポンスを標準出力に表示（デバッグ用）
-        println!("{:?}", category);
+    let client = Client::new(pool.clone());
+    let mut conn = pool.acquire().await?;
 
-        // category_idがない場合はスキップ
-        let Some(category_id) = &category.id else {
-            println!("⚠️ Skipped a category because id was missing.");
-            continue;
-        };
+    // カテゴリ一覧（IDと名前）を参照テーブルに反映
+    let list = client.get("/coins/categories/list", &[]).await?;
+    categories::store_list(&mut conn, &list).await?;
 
-        // market_cap, volume_24h を Option<f64> → Option<BigDecimal> に変換
-        let market_cap_bd = category.market_cap.and_then(BigDecimal::from_f64);
-        let volume_24h_bd = category.volume_24h.and_then(BigDecimal::from_f64);
+    // CoinGeckoのカテゴリーデータAPIからデータ取得
+    let params: Vec<(&str, &str)> = order.iter().map(|o| ("order", o.as_str())).collect();
+    let snapshot = client.get("/coins/categories", &params).await?;
 
-        // PostgreSQLのテーブルにデータを挿入
-        sqlx::query!(
-            r#"
-            INSERT INTO categories.category_market_data (
-                category_id,
-                name,
-                market_cap,
-                volume_24h,
-                fetched_at
-            )
-            VALUES ($1, $2, $3, $4, now())
-            "#,
-            category_id,
-            category.name,
-            market_cap_bd,
-            volume_24h_bd
-        )
-        .execute(&pool)
-        .await?;
-    }
+    // 各カテゴリデータをPostgreSQLのテーブルに挿入
+    categories::store(&mut conn, &snapshot).await?;
 
     // 挿入処理の完了ログ
     println!("✅ Successfully inserted into categories.category_market_data!");
     Ok(())
 }
-
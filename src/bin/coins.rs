@@ -1,118 +1,112 @@
 use dotenv::dotenv;
+use std::collections::HashMap;
 use std::env;
 use sqlx::PgPool;
-use serde::Deserialize;
-use chrono::NaiveDate;
-
-#[derive(Debug, Deserialize)]
-struct CoinListItem {
-    id: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct CoinDetail {
-    id: String,
-    symbol: Option<String>,
-    name: Option<String>,
-    hashing_algorithm: Option<String>,
-    description: Option<Description>,
-    links: Option<Links>,
-    genesis_date: Option<String>,
-    market_cap_rank: Option<i32>,
-}
+use coingecko::archive::Snapshot;
+use coingecko::coins::{self, CoinDetail, CoinListItem, DETAIL_LIMIT};
+use coingecko::contracts;
+use coingecko::{client::Client, pipeline, Error};
+
+// 取得済みの詳細をまとめて1トランザクションで
+// coins.detail と contract.token_info の両方に挿入
+// パースできなかったコインは failed に追加する
+async fn write_batch(
+    pool: &PgPool,
+    batch: &mut Vec<Snapshot>,
+    failed: &mut Vec<String>,
+) -> Result<usize, Error> {
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+
+    for snapshot in batch.drain(..) {
+        let id = snapshot.param("id").unwrap_or_default();
+        let (Ok(detail), Ok(contract)) = (
+            snapshot.parse::<CoinDetail>(),
+            snapshot.parse::<contracts::CoinDetail>(),
+        ) else {
+            println!("⚠️ Failed to parse coin detail: {}", id);
+            failed.push(id.to_string());
+            continue;
+        };
 
-#[derive(Debug, Deserialize)]
-struct Description {
-    en: Option<String>,
-}
+        coins::insert_detail(&mut tx, detail, snapshot.fetched_at).await?;
+        contracts::insert_token_info(&mut tx, &contract, snapshot.fetched_at).await?;
+        inserted += 1;
+    }
 
-#[derive(Debug, Deserialize)]
-struct Links {
-    homepage: Option<Vec<String>>,
+    tx.commit().await?;
+    Ok(inserted)
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Error> {
     // .envから環境変数を読み込み
     dotenv().ok();
     let database_url = env::var("DATABASE_URL")?;
     let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+
+    // コイン一覧をプラットフォーム付きで取得（※レスポンスが正常かチェック）
+    let list = match client.get("/coins/list", &[("include_platform", "true")]).await {
+        Ok(list) => list,
+        Err(e) => {
+            println!("❌ Failed to fetch coin list. Response: {}", e);
+            return Ok(());
+        }
+    };
 
-    // コイン一覧を取得（※レスポンスが正常かチェック）
-    let coin_list_url = "https://api.coingecko.com/api/v3/coins/list";
-    let res = reqwest::get(coin_list_url).await?;
+    let coin_list: Vec<CoinListItem> = list.parse()?;
 
-    if !res.status().is_success() {
-        let text = res.text().await?;
-        println!("❌ Failed to fetch coin list. Response: {}", text);
-        return Ok(());
+    // 詳細を取得しないコインは、一覧の platforms からコントラクトを挿入
+    let mut tx = pool.begin().await?;
+    for coin in coin_list.iter().skip(DETAIL_LIMIT) {
+        contracts::insert_listed(&mut tx, coin, list.fetched_at).await?;
     }
-
-    let coin_list: Vec<CoinListItem> = res.json().await?;
-
-    // 各コインの詳細を取得してDBに挿入
-    for coin in coin_list.iter().take(100) {
-        println!("📥 Inserting Coin: {:?}", coin.id);
-
-        let url = format!("https://api.coingecko.com/api/v3/coins/{}", coin.id);
-        let res = reqwest::get(&url).await?;
-
-        if !res.status().is_success() {
-            let text = res.text().await?;
-            println!("⚠️ Failed to fetch coin detail: {}, response: {}", coin.id, text);
-            continue;
+    tx.commit().await?;
+
+    // 先頭のコインは詳細を1回だけ並列に取得し、まとまった単位でDBに挿入
+    // 一覧の項目は詳細が取得・パースできなかったときのために残しておく
+    let ids: Vec<String> = coin_list.iter().take(DETAIL_LIMIT).map(|coin| coin.id.clone()).collect();
+    let mut top: HashMap<String, CoinListItem> = coin_list
+        .into_iter()
+        .take(DETAIL_LIMIT)
+        .map(|coin| (coin.id.clone(), coin))
+        .collect();
+    let mut results = pipeline::fetch_each(&client, "/coins/{id}", &[], ids);
+    let mut batch = Vec::new();
+    let mut failed = Vec::new();
+    let mut inserted = 0;
+
+    while let Some((id, result)) = results.recv().await {
+        match result {
+            Ok(snapshot) => {
+                println!("📥 Inserting Coin: {:?}", id);
+                batch.push(snapshot);
+            }
+            Err(e) => {
+                println!("⚠️ Failed to fetch coin detail: {}, response: {}", id, e);
+                failed.push(id);
+            }
         }
 
-        let Ok(detail) = res.json::<CoinDetail>().await else {
-            println!("⚠️ Failed to parse coin detail: {}", coin.id);
-            continue;
-        };
-
-        let homepage = detail.links
-            .and_then(|l| l.homepage)
-            .and_then(|vec| {
-                let filtered: Vec<String> = vec.into_iter().filter(|s| !s.is_empty()).collect();
-                if filtered.is_empty() {
-                    None
-                } else {
-                    Some(filtered)
-                }
-            });
-
-        let description = detail.description.and_then(|d| d.en);
-        let genesis_date = match detail.genesis_date {
-            Some(date_str) => NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok(),
-            None => None,
-        };
-
-        sqlx::query!(
-            r#"
-            INSERT INTO coins.detail (
-                id,
-                symbol,
-                name,
-                hashing_algorithm,
-                description,
-                homepage,
-                genesis_date,
-                market_cap_rank,
-                fetched_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())
-            "#,
-            detail.id,
-            detail.symbol,
-            detail.name,
-            detail.hashing_algorithm,
-            description,
-            homepage.as_deref(),
-            genesis_date,
-            detail.market_cap_rank
-        )
-        .execute(&pool)
-        .await?;
+        if batch.len() >= pipeline::BATCH_SIZE {
+            inserted += write_batch(&pool, &mut batch, &mut failed).await?;
+        }
+    }
+    inserted += write_batch(&pool, &mut batch, &mut failed).await?;
+
+    // 詳細が取れなかったコインは、一覧の platforms からコントラクトを挿入
+    if !failed.is_empty() {
+        let mut tx = pool.begin().await?;
+        for id in &failed {
+            if let Some(coin) = top.remove(id) {
+                contracts::insert_listed(&mut tx, &coin, list.fetched_at).await?;
+            }
+        }
+        tx.commit().await?;
+        println!("⚠️ Inserted contracts from the coin list for {} coins without detail", failed.len());
     }
 
-    println!("✅ Successfully inserted coins into coins.detail!");
+    println!("✅ Successfully inserted {} coins into coins.detail and contract.token_info!", inserted);
     Ok(())
 }
@@ -1,69 +1,70 @@
 use dotenv::dotenv;
 use std::env;
-use sqlx::{PgPool, types::BigDecimal};
-use num_traits::FromPrimitive;
-use serde::Deserialize;
+use sqlx::PgPool;
+use coingecko::{client::Client, derivatives, pipeline, Error};
 
-#[derive(Debug, Deserialize)]
-struct DerivativeMarket {
-    id: Option<String>,
-    symbol: Option<String>,
-    index_id: Option<String>,
-    price: Option<String>,
-    contract_type: Option<String>,
-}
+// ティッカーまで取得する取引所の数（一覧の先頭から、建玉の大きい順）
+const EXCHANGE_DETAIL_LIMIT: usize = 20;
+
+// /derivatives/exchanges の1ページあたりの件数
+const PER_PAGE: usize = 100;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Error> {
     // .envファイル読み込み → DB接続
     dotenv().ok();
     let database_url = env::var("DATABASE_URL")?;
     let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+    let mut conn = pool.acquire().await?;
 
     // CoinGecko API（デリバティブ市場）
-    let url = "https://api.coingecko.com/api/v3/derivatives";
-    let response = reqwest::get(url).await?.json::<Vec<DerivativeMarket>>().await?;
-
-    for market in response {
-        println!("{:?}", market); // ← デバッグ出力で中身を確認
+    let snapshot = client.get("/derivatives", &[]).await?;
+    let count = derivatives::store(&mut conn, &snapshot).await?;
+    println!(
+        "📊 Derivative contracts: {} stored, {} skipped",
+        count.stored, count.skipped
+    );
 
-        match &market.id {
-            Some(id) => {
-                // price: Option<String> → Option<f64> → Option<BigDecimal>
-                let price_bd = market
-                    .price
-                    .as_ref()
-                    .and_then(|p| p.parse::<f64>().ok())
-                    .and_then(BigDecimal::from_f64);
+    // デリバティブ取引所の一覧（ページがなくなるまで取得）
+    let mut exchange_ids = Vec::new();
+    let per_page = PER_PAGE.to_string();
+    for page in 1.. {
+        let page = page.to_string();
+        let snapshot = client
+            .get(
+                "/derivatives/exchanges",
+                &[("order", "open_interest_btc_desc"), ("per_page", &per_page), ("page", &page)],
+            )
+            .await?;
+        let ids = derivatives::store_exchanges(&mut conn, &snapshot).await?;
+        let last_page = ids.len() < PER_PAGE;
+        exchange_ids.extend(ids);
+        if last_page {
+            break;
+        }
+    }
 
-                sqlx::query!(
-                    r#"
-                    INSERT INTO derivatives.derivative_markets (
-                        id,
-                        symbol,
-                        index,
-                        price,
-                        contract_type,
-                        fetched_at
-                    )
-                    VALUES ($1, $2, $3, $4, $5, now())
-                    "#,
-                    id,
-                    market.symbol,
-                    market.index_id,
-                    price_bd,
-                    market.contract_type
-                )
-                .execute(&pool)
-                .await?;
-            }
-            None => {
-                // id がない場合はスキップ（ログは残す）
-                println!("⚠️ Skipped a market because id was missing.");
+    // 上位の取引所はティッカー（建玉・資金調達率）まで取得
+    let ids = exchange_ids.into_iter().take(EXCHANGE_DETAIL_LIMIT).collect();
+    let mut results = pipeline::fetch_each(
+        &client,
+        "/derivatives/exchanges/{id}",
+        &[("include_tickers", "all")],
+        ids,
+    );
+    while let Some((id, result)) = results.recv().await {
+        match result {
+            Ok(snapshot) => {
+                println!("📥 Inserting derivatives tickers: {:?}", id);
+                let mut tx = pool.begin().await?;
+                derivatives::store_exchange_tickers(&mut tx, &snapshot).await?;
+                tx.commit().await?;
             }
+            Err(e) => println!("⚠️ Failed to fetch derivatives exchange: {}: {}", id, e),
         }
     }
 
-    println!("✅ Successfully inserted into derivatives.derivative_markets!");
+    println!("✅ Successfully inserted into derivatives.derivative_markets, exchanges and exchange_tickers!");
     Ok(())
 }
@@ -0,0 +1,123 @@
+// 取引所ごとのBTC建て出来高の推移を exchanges.volume_chart にバックフィルする
+//
+// 使い方:
+//   cargo run --bin exchange_volume -- [--exchanges binance,gdax] [--days 365]
+//   cargo run --bin exchange_volume -- [--exchanges binance] --from 2025-01-01 --to 2025-07-01
+//
+// --exchanges を省略すると、直近の一覧で信頼スコア順位が上位の取引所を対象にする。
+// --from/--to を指定すると range エンドポイントを31日ずつに分けて呼び出す。
+use dotenv::dotenv;
+use std::env;
+use sqlx::PgPool;
+use chrono::Utc;
+use coingecko::archive::Snapshot;
+use coingecko::{chart, cli, client::Client, exchanges, Error};
+
+// --exchanges 省略時に対象とする取引所の数
+const DEFAULT_EXCHANGE_LIMIT: i64 = 10;
+
+async fn store(pool: &PgPool, snapshot: &Snapshot) -> Result<usize, Error> {
+    let mut tx = pool.begin().await?;
+    let count = exchanges::store_volume_chart(&mut tx, snapshot).await?;
+    tx.commit().await?;
+    Ok(count)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // .envから環境変数を読み込み
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL")?;
+    let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+
+    // 引数（すべて任意）
+    let mut exchange_ids: Option<Vec<String>> = None;
+    let mut days = "365".to_string();
+    let mut from = None;
+    let mut to = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--exchanges" => {
+                exchange_ids = Some(cli::split_list(&value));
+            }
+            "--days" => {
+                value.parse::<u32>()?;
+                days = value;
+            }
+            "--from" => from = Some(cli::parse_time(&value)?),
+            "--to" => to = Some(cli::parse_time(&value)?),
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+
+    // range を使う場合は --from が必須（--to 省略時は現在時刻まで）
+    let range = match (from, to) {
+        (Some(from), to) => Some((from, to.unwrap_or_else(Utc::now))),
+        (None, Some(_)) => return Err("--to requires --from".into()),
+        (None, None) => None,
+    };
+
+    // 対象の取引所（省略時は直近の一覧から）
+    let exchange_ids = match exchange_ids {
+        Some(ids) => ids,
+        None => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT id AS "id!"
+                FROM exchanges.exchange_info
+                WHERE id IS NOT NULL
+                  AND fetched_at >= (SELECT max(started_at) FROM exchanges.list_runs)
+                ORDER BY trust_score_rank NULLS LAST, id
+                LIMIT $1
+                "#,
+                DEFAULT_EXCHANGE_LIMIT
+            )
+            .fetch_all(&pool)
+            .await?
+        }
+    };
+    if exchange_ids.is_empty() {
+        println!("⚠️ No exchanges to backfill. Run `exchanges` first or pass --exchanges.");
+        return Ok(());
+    }
+
+    for exchange_id in &exchange_ids {
+        let mut stored = 0;
+
+        match range {
+            Some((from, to)) => {
+                for (start, end) in chart::windows(from, to, exchanges::VOLUME_RANGE_MAX_DAYS) {
+                    let start = start.timestamp().to_string();
+                    let end = end.timestamp().to_string();
+                    match client
+                        .get(
+                            "/exchanges/{id}/volume_chart/range",
+                            &[("id", exchange_id), ("from", &start), ("to", &end)],
+                        )
+                        .await
+                    {
+                        Ok(snapshot) => stored += store(&pool, &snapshot).await?,
+                        Err(e) => println!("⚠️ Failed to fetch volume chart for {}: {}", exchange_id, e),
+                    }
+                }
+            }
+            None => {
+                match client
+                    .get("/exchanges/{id}/volume_chart", &[("id", exchange_id), ("days", &days)])
+                    .await
+                {
+                    Ok(snapshot) => stored += store(&pool, &snapshot).await?,
+                    Err(e) => println!("⚠️ Failed to fetch volume chart for {}: {}", exchange_id, e),
+                }
+            }
+        }
+
+        println!("📊 Volume points for {}: {}", exchange_id, stored);
+    }
+
+    println!("✅ Successfully backfilled exchanges.volume_chart!");
+    Ok(())
+}
@@ -1,61 +1,94 @@
 use dotenv::dotenv;
 use std::env;
-use sqlx::{PgPool, types::BigDecimal};
-use num_traits::FromPrimitive;
-use serde::Deserialize;
-
-#[derive(Debug, Deserialize)]
-struct Exchange {
-    id: String,
-    name: Option<String>,
-    year_established: Option<i32>,
-    country: Option<String>,
-    trade_volume_24h_btc: Option<f64>,
-    trust_score: Option<i32>,
-}
+use sqlx::PgPool;
+use coingecko::{cli, client::Client, exchanges, Error};
+
+// ティッカーまで取得する取引所の数（一覧の先頭から）
+const TICKER_EXCHANGE_LIMIT: usize = 10;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Error> {
     // .envから環境変数を読み込み
     dotenv().ok();
     let database_url = env::var("DATABASE_URL")?;
     let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+    let mut conn = pool.acquire().await?;
+
+    // ティッカーを取得する取引所（--exchanges binance,gdax、省略時は一覧の先頭）
+    let mut ticker_exchanges: Option<Vec<String>> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--exchanges" => {
+                let value = args.next().ok_or("missing value for --exchanges")?;
+                ticker_exchanges = Some(cli::split_list(&value));
+            }
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+
+    // CoinGecko APIから取引所情報をページがなくなるまで取得し、各取引所をDBに挿入
+    let mut ids = Vec::new();
+    let mut started_at = None;
+    let mut finished_at = None;
+    let per_page = exchanges::PER_PAGE.to_string();
+    for page in 1.. {
+        let page = page.to_string();
+        let snapshot = client
+            .get("/exchanges", &[("per_page", &per_page), ("page", &page)])
+            .await?;
+        started_at.get_or_insert(snapshot.fetched_at);
+        finished_at = Some(snapshot.fetched_at);
+
+        let page_ids = exchanges::store(&mut conn, &snapshot).await?;
+        let last_page = page_ids.len() < exchanges::PER_PAGE;
+        ids.extend(page_ids);
+        if last_page {
+            break;
+        }
+    }
+
+    // 一覧を取得し終えたら、前回の一覧から消えた取引所を報告
+    if let (Some(started_at), Some(finished_at)) = (started_at, finished_at) {
+        let disappeared =
+            exchanges::record_list_run(&mut conn, started_at, finished_at, ids.len()).await?;
+        println!("📊 Exchanges listed: {}, disappeared since last run: {}", ids.len(), disappeared.len());
+        for exchange_id in disappeared {
+            println!("⚠️ Exchange disappeared from /exchanges: {}", exchange_id);
+        }
+    }
+    let ticker_exchanges =
+        ticker_exchanges.unwrap_or_else(|| ids.into_iter().take(TICKER_EXCHANGE_LIMIT).collect());
+
+    // 取引所ごとにティッカーをページがなくなるまで取得
+    for exchange_id in &ticker_exchanges {
+        let mut stored = 0;
+        for page in 1.. {
+            let page = page.to_string();
+            let snapshot = match client
+                .get("/exchanges/{id}/tickers", &[("id", exchange_id), ("page", &page)])
+                .await
+            {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    println!("⚠️ Failed to fetch tickers for {} (page {}): {}", exchange_id, page, e);
+                    break;
+                }
+            };
+
+            let mut tx = pool.begin().await?;
+            let count = exchanges::store_tickers(&mut tx, &snapshot).await?;
+            tx.commit().await?;
 
-    // CoinGecko APIから取引所情報を取得
-    let url = "https://api.coingecko.com/api/v3/exchanges";
-    let exchanges: Vec<Exchange> = reqwest::get(url).await?.json().await?;
-
-    // 各取引所をDBに挿入
-    for exchange in exchanges {
-        println!("📥 Inserting Exchange: {:?}", exchange);
-
-        let volume_bd = exchange.trade_volume_24h_btc
-            .and_then(BigDecimal::from_f64);
-
-        sqlx::query!(
-            r#"
-            INSERT INTO exchanges.exchange_info (
-                id,
-                name,
-                year_established,
-                country,
-                trade_volume_24h_btc,
-                trust_score,
-                fetched_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, now())
-            "#,
-            exchange.id,
-            exchange.name,
-            exchange.year_established,
-            exchange.country,
-            volume_bd,
-            exchange.trust_score
-        )
-        .execute(&pool)
-        .await?;
+            stored += count;
+            if count < exchanges::TICKERS_PER_PAGE {
+                break;
+            }
+        }
+        println!("📊 Tickers on {}: {}", exchange_id, stored);
     }
 
-    println!("✅ Successfully inserted exchanges into exchanges.exchange_info!");
+    println!("✅ Successfully inserted exchanges into exchanges.exchange_info and exchanges.tickers!");
     Ok(())
 }
@@ -0,0 +1,115 @@
+// 指定時点のスナップショットで年率換算した資金調達率が高い／低い無期限先物を一覧表示する
+// 年率は derivatives.funding_intervals の間隔（時間）で換算する
+//
+// 使い方:
+//   cargo run --bin funding -- [--at 2026-01-01T00:00:00Z] [--limit 10]
+//
+// --at を省略すると最新のスナップショット、指定するとその時刻以前で最新のものを使う。
+use dotenv::dotenv;
+use std::env;
+use sqlx::PgPool;
+use chrono::Utc;
+use std::collections::BTreeSet;
+use coingecko::{cli, derivatives, Error};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // .envから環境変数を読み込み
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL")?;
+    let pool = PgPool::connect(&database_url).await?;
+
+    // 引数（どちらも任意）
+    let mut at = Utc::now();
+    let mut limit: usize = 10;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--at" => at = cli::parse_time(&value)?,
+            "--limit" => limit = value.parse()?,
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+
+    // 対象のスナップショット
+    let Some(snapshot_at) = sqlx::query_scalar!(
+        "SELECT max(fetched_at) FROM derivatives.funding_rates WHERE fetched_at <= $1",
+        at
+    )
+    .fetch_one(&pool)
+    .await?
+    else {
+        println!("⚠️ No funding rates found at or before {}", at);
+        return Ok(());
+    };
+
+    println!("📊 Funding rates at {}", snapshot_at);
+
+    // 銘柄ごとの間隔、無ければ取引所の既定値で年率換算する
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            f.market,
+            f.symbol,
+            f.funding_rate,
+            f.basis,
+            coalesce(
+                (SELECT i.interval_hours FROM derivatives.funding_intervals i
+                 WHERE i.market = f.market AND i.symbol = f.symbol),
+                (SELECT i.interval_hours FROM derivatives.funding_intervals i
+                 WHERE i.market = f.market AND i.symbol = '')
+            ) AS interval_hours
+        FROM derivatives.funding_rates f
+        WHERE f.fetched_at = $1
+        "#,
+        snapshot_at
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut assumed = BTreeSet::new();
+    let mut annualized: Vec<_> = rows
+        .into_iter()
+        .map(|row| {
+            let interval_hours = row.interval_hours.unwrap_or_else(|| {
+                assumed.insert(row.market.clone());
+                derivatives::DEFAULT_FUNDING_INTERVAL_HOURS
+            });
+            let rate = derivatives::annualize_funding(&row.funding_rate, interval_hours);
+            (rate, interval_hours, row)
+        })
+        .collect();
+    annualized.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let limit = limit.min(annualized.len());
+    let highest = annualized.iter().take(limit);
+    let lowest = annualized.iter().rev().take(limit);
+    for (label, rows) in [("Highest", highest.collect::<Vec<_>>()), ("Lowest", lowest.collect())] {
+        println!("\n{} annualized funding:", label);
+        for (rate, interval_hours, row) in rows {
+            println!(
+                "  {:<24} {:<16} {:>10}% / {}h{}  {:>10}% / year  basis {}",
+                row.market,
+                row.symbol,
+                row.funding_rate.round(4),
+                interval_hours,
+                if row.interval_hours.is_none() { "*" } else { "" },
+                rate.round(2),
+                row.basis.as_ref().map(|b| b.round(4).to_string()).unwrap_or_else(|| "-".to_string())
+            );
+        }
+    }
+
+    // 間隔が未設定の取引所は既定値を仮定しているので知らせる
+    if !assumed.is_empty() {
+        println!(
+            "\n⚠️ * Funding interval assumed to be {}h for {} markets without a row in derivatives.funding_intervals: {}",
+            derivatives::DEFAULT_FUNDING_INTERVAL_HOURS,
+            assumed.len(),
+            assumed.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// 全体の時価総額・出来高の日次系列を global.daily_market_cap にバックフィルする
+//
+// 使い方:
+//   cargo run --bin global_history -- [--days 365] [--vs-currency usd] [--from-snapshots]
+//
+// /global/market_cap_chart は有料プランのみのため、取得に失敗した場合や
+// --from-snapshots を指定した場合は、保存済みの /global のスナップショットから再構成する。
+use dotenv::dotenv;
+use std::env;
+use sqlx::PgPool;
+use coingecko::{client::Client, global, Error};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // .envから環境変数を読み込み
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL")?;
+    let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+
+    // 引数（すべて任意）
+    let mut days = "365".to_string();
+    let mut vs_currency = "usd".to_string();
+    let mut from_snapshots = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--days" => {
+                let value = args.next().ok_or("missing value for --days")?;
+                value.parse::<u32>()?;
+                days = value;
+            }
+            "--vs-currency" => {
+                vs_currency = args.next().ok_or("missing value for --vs-currency")?.to_lowercase();
+            }
+            "--from-snapshots" => from_snapshots = true,
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+
+    if !from_snapshots {
+        match client
+            .get("/global/market_cap_chart", &[("days", &days), ("vs_currency", &vs_currency)])
+            .await
+        {
+            Ok(snapshot) => {
+                let mut tx = pool.begin().await?;
+                let count = global::store_market_cap_chart(&mut tx, &snapshot).await?;
+                tx.commit().await?;
+                println!("✅ Backfilled {} days from /global/market_cap_chart!", count);
+                return Ok(());
+            }
+            Err(e) => {
+                println!("⚠️ Failed to fetch global market cap chart, reconstructing from snapshots: {}", e);
+            }
+        }
+    }
+
+    // 保存済みのスナップショットから再構成
+    let mut tx = pool.begin().await?;
+    let count = global::rebuild_daily_from_snapshots(&mut tx, &vs_currency).await?;
+    tx.commit().await?;
+
+    println!("✅ Reconstructed {} days into global.daily_market_cap from stored snapshots!", count);
+    Ok(())
+}
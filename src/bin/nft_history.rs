@@ -0,0 +1,101 @@
+// NFTコレクションのフロア価格・時価総額・出来高の推移を nfts.market_chart にバックフィルする
+//
+// 使い方:
+//   cargo run --bin nft_history -- [--collections bored-ape-yacht-club,pudgy-penguins] [--days 365]
+//   cargo run --bin nft_history -- --contracts ethereum:0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d
+//
+// --collections を省略すると、直近に取得した nfts.collections のうち時価総額（USD）上位を対象にする。
+use dotenv::dotenv;
+use std::env;
+use sqlx::PgPool;
+use coingecko::{cli, client::Client, nfts, Error};
+
+// --collections 省略時に対象とするコレクションの数
+const DEFAULT_COLLECTION_LIMIT: i64 = 10;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // .envから環境変数を読み込み
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL")?;
+    let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+
+    // 引数（すべて任意）
+    let mut collection_ids: Option<Vec<String>> = None;
+    let mut contracts = Vec::new();
+    let mut days = "365".to_string();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--collections" => {
+                collection_ids = Some(cli::split_list(&value));
+            }
+            "--contracts" => contracts = cli::split_pairs(&value, nfts::CONTRACT_FORMAT)?,
+            "--days" => {
+                value.parse::<u32>()?;
+                days = value;
+            }
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+
+    // コントラクトで指定されたコレクションはIDに解決（解決済みの対応はキャッシュされる）
+    let mut conn = pool.acquire().await?;
+    for (platform, address) in &contracts {
+        match nfts::resolve_contract(&client, &mut conn, platform, address).await {
+            Ok(id) => collection_ids.get_or_insert_with(Vec::new).push(id),
+            Err(e) => println!("⚠️ Failed to resolve NFT contract {}:{}: {}", platform, address, e),
+        }
+    }
+
+    // 対象のコレクション（省略時はコレクションごとの最新の取得から時価総額順）
+    let collection_ids = match collection_ids {
+        Some(ids) => ids,
+        None if !contracts.is_empty() => Vec::new(),
+        None => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT id AS "id!"
+                FROM (
+                    SELECT DISTINCT ON (id) id, market_cap_usd
+                    FROM nfts.collections
+                    WHERE id IS NOT NULL
+                    ORDER BY id, fetched_at DESC
+                ) latest
+                ORDER BY market_cap_usd DESC NULLS LAST, id
+                LIMIT $1
+                "#,
+                DEFAULT_COLLECTION_LIMIT
+            )
+            .fetch_all(&mut *conn)
+            .await?
+        }
+    };
+    if collection_ids.is_empty() {
+        println!("⚠️ No NFT collections to backfill. Run `nfts` first or pass --collections.");
+        return Ok(());
+    }
+
+    for collection_id in &collection_ids {
+        let snapshot = match client
+            .get("/nfts/{id}/market_chart", &[("id", collection_id), ("days", &days)])
+            .await
+        {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                println!("⚠️ Failed to fetch NFT market chart for {}: {}", collection_id, e);
+                continue;
+            }
+        };
+
+        let mut tx = pool.begin().await?;
+        let count = nfts::store_market_chart(&mut tx, &snapshot).await?;
+        tx.commit().await?;
+        println!("📊 Market chart points for {}: {}", collection_id, count);
+    }
+
+    println!("✅ Successfully backfilled nfts.market_chart!");
+    Ok(())
+}
@@ -2,35 +2,41 @@
 use dotenv::dotenv;
 // 環境変数の取得やSQL接続に必要な標準ライブラリ
 use std::env;
-// PostgreSQL用の非同期接続とBigDecimal型
-use sqlx::{PgPool, types::BigDecimal};
-// f64 → BigDecimalへの変換のため
-use num_traits::FromPrimitive;
-// JSONデシリアライズ用
-use serde::Deserialize;
-// floor_priceやvolume_24hの連想配列に対応
-use std::collections::HashMap;
-// APIレート制限を避けるための待機処理
-use tokio::time::{sleep, Duration};
-
-// NFT一覧取得用の構造体（/nfts/list の1件分）
-#[derive(Debug, Deserialize)]
-struct NftListItem {
-    id: String,
-}
+// PostgreSQL用の非同期接続
+use sqlx::PgPool;
+// API取得と生レスポンスのアーカイブ用
+use coingecko::archive::Snapshot;
+use coingecko::{cli, client::Client, pipeline, Error};
+// レスポンスの構造体と nfts.collections への変換処理
+use coingecko::nfts::{self, NftDetail, NftListItem};
+
+// 全コレクション巡回の位置の名前（nfts.refresh_cursor）
+const CURSOR: &str = "all";
+
+// 1回の実行で詳細を取得するコレクション数の既定値
+const DEFAULT_LIMIT: usize = 100;
+
+// 取得済みの詳細をまとめて1トランザクションで挿入
+async fn write_batch(pool: &PgPool, batch: &mut Vec<Snapshot>) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+
+    for snapshot in batch.drain(..) {
+        // 正常にパースできなければスキップ
+        let Ok(detail) = snapshot.parse::<NftDetail>() else {
+            println!("⚠️ Failed to parse NFT: {}", snapshot.param("id").unwrap_or_default());
+            continue;
+        };
+
+        // 取得した情報を nfts.collections テーブルにINSERT
+        nfts::insert_collection(&mut tx, &detail, snapshot.fetched_at).await?;
+    }
 
-// 各NFTの詳細情報の構造体（/nfts/{id} のレスポンス構造）
-#[derive(Debug, Deserialize)]
-struct NftDetail {
-    id: String,
-    name: Option<String>,
-    symbol: Option<String>,
-    floor_price: Option<HashMap<String, f64>>,
-    volume_24h: Option<HashMap<String, f64>>,
+    tx.commit().await?;
+    Ok(())
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Error> {
     // .envファイルから環境変数（DATABASE_URLなど）を読み込む
     dotenv().ok();
 
@@ -39,64 +45,107 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // PostgreSQLとの非同期接続プールを作成
     let pool = PgPool::connect(&database_url).await?;
+    let client = Client::new(pool.clone());
+    let mut conn = pool.acquire().await?;
 
-    // CoinGecko APIからNFT一覧を取得（最大数を制限するため Vec にパース）
-    let nft_list: Vec<NftListItem> = reqwest::get("https://api.coingecko.com/api/v3/nfts/list")
-        .await?
-        .json()
-        .await?;
-
-    // 取得したNFTの最初の10件だけ処理
-    for nft in nft_list.iter().take(10) {
-        // 各NFTの詳細情報APIのURLを組み立て
-        let detail_url = format!("https://api.coingecko.com/api/v3/nfts/{}", nft.id);
-        
-        // 詳細情報を取得
-        let res = reqwest::get(&detail_url).await?;
+    // 引数（すべて任意）
+    //   --order h24_volume_usd_desc  一覧の並び順
+    //   --limit 100                  全件巡回で今回詳細を取得する件数
+    //   --platforms ethereum,solana  対象をプラットフォームで絞る
+    //   --contracts ethereum:0xabc   対象を (プラットフォーム:コントラクト) で指定する
+    //   --top 50                     一覧の並び順で先頭から絞る
+    // 絞り込みを指定しなければ、前回の続きから全コレクションを順に巡回する
+    let mut order = None;
+    let mut limit = DEFAULT_LIMIT;
+    let mut platforms: Option<Vec<String>> = None;
+    let mut contracts: Option<Vec<(String, String)>> = None;
+    let mut top: Option<usize> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--order" => order = Some(value),
+            "--limit" => limit = value.parse()?,
+            "--platforms" => platforms = Some(cli::split_list(&value.to_lowercase())),
+            "--contracts" => contracts = Some(cli::split_pairs(&value, nfts::CONTRACT_FORMAT)?),
+            "--top" => top = Some(value.parse()?),
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+    if let Some(order) = &order
+        && !nfts::ORDERS.contains(&order.as_str())
+    {
+        return Err(format!("--order must be one of {}", nfts::ORDERS.join(", ")).into());
+    }
 
-        // 正常にパースできなければスキップ
-        let Ok(detail) = res.json::<NftDetail>().await else {
-            println!("⚠️ Failed to parse NFT: {}", nft.id);
-            continue;
-        };
+    // CoinGecko APIからNFT一覧をページがなくなるまで取得
+    let mut nft_list: Vec<NftListItem> = Vec::new();
+    let per_page = nfts::LIST_PER_PAGE.to_string();
+    for page in 1.. {
+        let page = page.to_string();
+        let mut params = vec![("per_page", per_page.as_str()), ("page", page.as_str())];
+        if let Some(order) = &order {
+            params.push(("order", order));
+        }
+        let snapshot = client.get("/nfts/list", &params).await?;
+        let items = nfts::store_list(&mut conn, &snapshot).await?;
+        let last_page = items.len() < nfts::LIST_PER_PAGE;
+        nft_list.extend(items);
+        if last_page {
+            break;
+        }
+    }
+    println!("📊 NFT collections listed: {}", nft_list.len());
 
-        // デバッグ出力（取得したNFTの詳細）
-        println!("📥 Inserting NFT: {:?}", detail);
+    // 詳細を取得するコレクション
+    let subset = platforms.is_some() || contracts.is_some() || top.is_some();
+    let ids: Vec<String> = if subset {
+        // プラットフォーム・上位件数の指定は一覧から選ぶ
+        let mut ids: Vec<String> = Vec::new();
+        if platforms.is_some() || top.is_some() {
+            let selected = nft_list.into_iter().filter(|nft| {
+                let platform = nft.asset_platform_id.as_deref().unwrap_or_default().to_lowercase();
+                platforms.as_ref().is_none_or(|p| p.contains(&platform))
+            });
+            ids.extend(selected.take(top.unwrap_or(usize::MAX)).map(|nft| nft.id));
+        }
+        // コントラクトの指定はIDに解決して加える（解決済みの対応はキャッシュされる）
+        for (platform, address) in contracts.iter().flatten() {
+            match nfts::resolve_contract(&client, &mut conn, platform, address).await {
+                Ok(id) if !ids.contains(&id) => ids.push(id),
+                Ok(_) => {}
+                Err(e) => println!("⚠️ Failed to resolve NFT contract {}:{}: {}", platform, address, e),
+            }
+        }
+        ids
+    } else {
+        // 前回の位置の次から limit 件（末尾まで来たら先頭に戻る）
+        let mut all: Vec<String> = nft_list.into_iter().map(|nft| nft.id).collect();
+        all.sort();
+        all.dedup();
+        let cursor = nfts::load_cursor(&mut conn, CURSOR).await?;
+        let start = cursor.map_or(0, |last| all.partition_point(|id| id.as_str() <= last.as_str()));
+        all.iter().cycle().skip(start).take(limit.min(all.len())).cloned().collect()
+    };
+    println!("📊 NFT collections to refresh: {}", ids.len());
 
-        // floor_price["usd"] を BigDecimal に変換
-        let floor_price = detail.floor_price.as_ref()
-            .and_then(|map| map.get("usd"))
-            .and_then(|v| BigDecimal::from_f64(*v));
+    // 詳細情報をまとまった単位ごとに並列に取得（APIレート制限はクライアント側で調整）
+    // 全件巡回では単位ごとに位置を記録するので、中断しても次回はその続きから再開できる
+    for chunk in ids.chunks(pipeline::BATCH_SIZE) {
+        let mut results = pipeline::fetch_each(&client, "/nfts/{id}", &[], chunk.to_vec());
+        let mut batch = Vec::new();
 
-        // volume_24h["usd"] を BigDecimal に変換
-        let volume_24h = detail.volume_24h.as_ref()
-            .and_then(|map| map.get("usd"))
-            .and_then(|v| BigDecimal::from_f64(*v));
+        while let Some((id, result)) = results.recv().await {
+            match result {
+                Ok(snapshot) => batch.push(snapshot),
+                Err(e) => println!("⚠️ Failed to fetch NFT: {}: {}", id, e),
+            }
+        }
+        write_batch(&pool, &mut batch).await?;
 
-        // 取得した情報を nfts.collections テーブルにINSERT
-        sqlx::query!(
-            r#"
-            INSERT INTO nfts.collections (
-                id,
-                name,
-                floor_price,
-                volume_24h,
-                symbol,
-                fetched_at
-            )
-            VALUES ($1, $2, $3, $4, $5, now())
-            "#,
-            detail.id,
-            detail.name,
-            floor_price,
-            volume_24h,
-            detail.symbol
-        )
-        .execute(&pool)
-        .await?;
-
-        // APIレート制限対策のため、1秒間隔で次のリクエストを送る
-        sleep(Duration::from_millis(1000)).await;
+        if !subset {
+            nfts::save_cursor(&mut conn, CURSOR, chunk.last().map(String::as_str)).await?;
+        }
     }
 
     // 処理完了ログ
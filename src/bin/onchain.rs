@@ -1,53 +1,84 @@
+// DEX Screener から追跡中のペアの価格・出来高を取得する
+//
+// 使い方:
+//   cargo run --bin onchain -- [--pairs ethereum:0x8ad5...,solana:XYZ]
+//
+// 対象は --pairs、環境変数 ONCHAIN_PAIRS（同じ形式）、onchain.watchlist の順に決まる。
+// 同じチェーンのペアは PAIRS_PER_REQUEST 件ずつまとめて1回で取得する。
 use dotenv::dotenv;
+use std::collections::BTreeMap;
 use std::env;
-use sqlx::{PgPool, types::BigDecimal};
-use num_traits::FromPrimitive;
-use serde_json::Value;
+use sqlx::PgPool;
+use coingecko::{cli, client::Client, onchain, Error, DEXSCREENER_API};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Error> {
     // .env読み込み & DB接続
     dotenv().ok();
     let database_url = env::var("DATABASE_URL")?;
     let pool = PgPool::connect(&database_url).await?;
+    let client = Client::with_api(pool.clone(), DEXSCREENER_API);
+    let mut conn = pool.acquire().await?;
 
-    // DEX Screener API
-    let url = "https://api.dexscreener.com/latest/dex/pairs/ethereum/0x8ad599c3a0ff1de082011efddc58f1908eb6e6d8";
+    // 対象のペア
+    let mut pairs = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pairs" => {
+                let value = args.next().ok_or("missing value for --pairs")?;
+                pairs = Some(cli::split_pairs(&value, onchain::PAIR_FORMAT)?);
+            }
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+    let pairs = match pairs {
+        Some(pairs) => pairs,
+        None => match env::var("ONCHAIN_PAIRS") {
+            Ok(value) => cli::split_pairs(&value, onchain::PAIR_FORMAT)?,
+            Err(_) => onchain::load_watchlist(&mut conn).await?,
+        },
+    };
+    if pairs.is_empty() {
+        println!("⚠️ No pairs to track. Add rows to onchain.watchlist or pass --pairs.");
+        return Ok(());
+    }
 
-    // API呼び出し & JSON取得
-    let resp = reqwest::get(url).await?.text().await?;
-    let json: Value = serde_json::from_str(&resp)?;
+    // チェーンごとにまとめる
+    let mut by_chain: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (chain, pair) in pairs {
+        by_chain.entry(chain).or_default().push(pair);
+    }
 
-    // データ抽出
-    let pair = &json["pair"];
-    let exchange = pair["dexId"].as_str().unwrap_or("unknown");
-    let token_address = pair["baseToken"]["address"].as_str().unwrap_or("unknown");
-    let price = pair["priceUsd"].as_str().unwrap_or("0.0").parse::<f64>().unwrap_or(0.0);
-    let liquidity = pair["liquidity"]["usd"].as_str().unwrap_or("0.0").parse::<f64>().unwrap_or(0.0);
+    let mut report = onchain::ValidationReport::default();
+    for (chain, addresses) in &by_chain {
+        for chunk in addresses.chunks(onchain::PAIRS_PER_REQUEST) {
+            // DEX Screener API呼び出し & JSON取得
+            let joined = chunk.join(",");
+            let snapshot = match client
+                .get("/latest/dex/pairs/{chain}/{pair}", &[("chain", chain), ("pair", &joined)])
+                .await
+            {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    println!("⚠️ Failed to fetch DEX pairs on {}: {}", chain, e);
+                    continue;
+                }
+            };
 
-    let price_bd = BigDecimal::from_f64(price);
-    let liquidity_bd = BigDecimal::from_f64(liquidity);
+            // データ抽出 & 挿入
+            let mut tx = pool.begin().await?;
+            report.merge(&onchain::store(&mut tx, &snapshot).await?);
+            tx.commit().await?;
+        }
+    }
 
-    // データ挿入
-    sqlx::query!(
-        r#"
-        INSERT INTO onchain.dex_token_prices (
-            exchange,
-            token_address,
-            price,
-            liquidity_usd,
-            fetched_at
-        )
-        VALUES ($1, $2, $3, $4, now())
-        "#,
-        exchange,
-        token_address,
-        price_bd,
-        liquidity_bd
-    )
-    .execute(&pool)
-    .await?;
+    // 検証結果
+    println!(
+        "📊 DEX pairs: {} stored, {} rejected (see onchain.rejected_pairs), {} without price, {} without liquidity",
+        report.stored, report.rejected, report.missing_price, report.missing_liquidity
+    );
 
-    println!("✅ Successfully inserted DEX Screener data into onchain.dex_token_prices!");
+    println!("✅ Successfully inserted {} DEX Screener pairs into onchain.dex_token_prices!", report.stored);
     Ok(())
 }
@@ -0,0 +1,123 @@
+// raw.responses のアーカイブから正規化テーブルを作り直す
+//
+// 使い方:
+//   cargo run --bin rederive -- --from 2026-01-01 --to 2026-02-01 [--endpoint /coins/{id}]
+//
+// 期間内の各スナップショットについて、同じ fetched_at の行を削除してから
+// 現在の変換処理で挿入し直す。行が無ければそのまま追加（バックフィル）になる。
+use dotenv::dotenv;
+use std::env;
+use sqlx::{PgConnection, PgPool};
+use coingecko::archive::{self, Snapshot};
+use coingecko::{
+    categories, cli, coins, companies, contracts, derivatives, exchanges, global, nfts, onchain,
+    platform, price, search, Error,
+};
+
+// 一度に読み出すアーカイブの件数
+const PAGE_SIZE: i64 = 500;
+
+// エンドポイントに対応する変換処理を実行する（対象外なら false）
+async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<bool, Error> {
+    match snapshot.endpoint.as_str() {
+        "/coins/list" if snapshot.param("include_platform") == Some("true") => {
+            contracts::rebuild_listed(conn, snapshot).await?;
+        }
+        "/coins/{id}" => {
+            coins::rebuild_detail(conn, snapshot).await?;
+            contracts::rebuild_token_info(conn, snapshot).await?;
+        }
+        "/coins/categories" => categories::rebuild(conn, snapshot).await?,
+        "/coins/categories/list" => categories::store_list(conn, snapshot).await?,
+        "/exchanges" => exchanges::rebuild(conn, snapshot).await?,
+        "/exchanges/{id}/tickers" => exchanges::rebuild_tickers(conn, snapshot).await?,
+        "/exchanges/{id}/volume_chart" | "/exchanges/{id}/volume_chart/range" => {
+            exchanges::store_volume_chart(conn, snapshot).await?;
+        }
+        "/companies/public_treasury/{coin_id}" => companies::rebuild(conn, snapshot).await?,
+        "/search/trending" => search::rebuild(conn, snapshot).await?,
+        "/simple/price" => price::rebuild(conn, snapshot).await?,
+        "/derivatives" => derivatives::rebuild(conn, snapshot).await?,
+        "/derivatives/exchanges" => derivatives::rebuild_exchanges(conn, snapshot).await?,
+        "/derivatives/exchanges/{id}" => derivatives::rebuild_exchange_tickers(conn, snapshot).await?,
+        "/global" => global::rebuild(conn, snapshot).await?,
+        "/global/decentralized_finance_defi" => global::rebuild_defi(conn, snapshot).await?,
+        "/global/market_cap_chart" => {
+            global::store_market_cap_chart(conn, snapshot).await?;
+        }
+        "/nfts/list" => {
+            nfts::store_list(conn, snapshot).await?;
+        }
+        "/nfts/{id}" => nfts::rebuild_collection(conn, snapshot).await?,
+        "/nfts/{asset_platform_id}/contract/{contract_address}" => {
+            nfts::rebuild_contract(conn, snapshot).await?;
+        }
+        "/nfts/{id}/market_chart" => {
+            nfts::store_market_chart(conn, snapshot).await?;
+        }
+        "/latest/dex/pairs/{chain}/{pair}" => onchain::rebuild(conn, snapshot).await?,
+        "/asset_platforms" => platform::rebuild(conn, snapshot).await?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // .envから環境変数を読み込み
+    dotenv().ok();
+    let database_url = env::var("DATABASE_URL")?;
+    let pool = PgPool::connect(&database_url).await?;
+
+    // 引数（--from, --to は必須、--endpoint は任意）
+    let mut from = None;
+    let mut to = None;
+    let mut endpoint = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {}", arg))?;
+        match arg.as_str() {
+            "--from" => from = Some(cli::parse_time(&value)?),
+            "--to" => to = Some(cli::parse_time(&value)?),
+            "--endpoint" => endpoint = Some(value),
+            _ => return Err(format!("unknown argument: {}", arg).into()),
+        }
+    }
+    let from = from.ok_or("--from is required")?;
+    let to = to.ok_or("--to is required")?;
+
+    let mut rebuilt = 0;
+    let mut skipped = 0;
+    let mut failed = 0;
+    let mut after_id = 0;
+
+    loop {
+        let page = archive::load_page(&pool, endpoint.as_deref(), from, to, after_id, PAGE_SIZE).await?;
+        let Some((last_id, _)) = page.last() else {
+            break;
+        };
+        after_id = *last_id;
+
+        for (id, snapshot) in page {
+            // スナップショット単位でトランザクションを分け、失敗しても次に進む
+            let mut tx = pool.begin().await?;
+            match rebuild(&mut tx, &snapshot).await {
+                Ok(true) => {
+                    tx.commit().await?;
+                    rebuilt += 1;
+                }
+                Ok(false) => skipped += 1,
+                Err(e) => {
+                    println!("⚠️ Failed to rebuild {} (raw id {}): {}", snapshot.endpoint, id, e);
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!(
+        "✅ Rebuilt {} snapshots from raw.responses ({} skipped, {} failed)",
+        rebuilt, skipped, failed
+    );
+    Ok(())
+}
@@ -0,0 +1,175 @@
+// /coins/categories のレスポンスを categories.category_market_data に変換する
+// あわせてカテゴリ一覧（参照テーブル）とコインの所属カテゴリも扱う
+use chrono::{DateTime, Utc};
+use num_traits::FromPrimitive;
+use serde::Deserialize;
+use sqlx::{PgConnection, types::BigDecimal};
+
+use crate::{archive::Snapshot, Error};
+
+// APIレスポンスのカテゴリーデータを受け取るための構造体
+#[derive(Debug, Deserialize)]
+pub struct Category {
+    id: Option<String>,           // カテゴリーID（API上のID）
+    name: Option<String>,         // カテゴリー名
+    market_cap: Option<f64>,      // 時価総額（USD）
+    market_cap_change_24h: Option<f64>, // 時価総額の24時間変化率（%）
+    volume_24h: Option<f64>,      // 24時間の取引量（USD）
+    top_3_coins_id: Option<Vec<String>>, // 上位3コインのID
+    top_3_coins: Option<Vec<String>>,    // 上位3コインの画像URL
+    content: Option<String>,      // カテゴリの説明
+    updated_at: Option<String>,   // CoinGecko側の更新日時
+}
+
+// /coins/categories の order に指定できる値
+pub const ORDERS: [&str; 6] = [
+    "market_cap_desc",
+    "market_cap_asc",
+    "name_desc",
+    "name_asc",
+    "market_cap_change_24h_desc",
+    "market_cap_change_24h_asc",
+];
+
+// /coins/categories/list の1件分
+#[derive(Debug, Deserialize)]
+struct CategoryListItem {
+    category_id: String,
+    name: String,
+}
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let response = snapshot.parse::<Vec<Category>>()?;
+
+    // 各カテゴリデータを1件ずつ処理
+    for category in response {
+        // レスポンスを標準出力に表示（デバッグ用）
+        println!("{:?}", category);
+
+        // category_idがない場合はスキップ
+        let Some(category_id) = &category.id else {
+            println!("⚠️ Skipped a category because id was missing.");
+            continue;
+        };
+
+        // market_cap, volume_24h を Option<f64> → Option<BigDecimal> に変換
+        let market_cap_bd = category.market_cap.and_then(BigDecimal::from_f64);
+        let market_cap_change_24h_bd = category.market_cap_change_24h.and_then(BigDecimal::from_f64);
+        let volume_24h_bd = category.volume_24h.and_then(BigDecimal::from_f64);
+
+        // updated_at は RFC3339 形式の文字列
+        let updated_at = category.updated_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+
+        // PostgreSQLのテーブルにデータを挿入
+        sqlx::query!(
+            r#"
+            INSERT INTO categories.category_market_data (
+                category_id,
+                name,
+                market_cap,
+                market_cap_change_24h,
+                volume_24h,
+                top_3_coin_ids,
+                top_3_coins,
+                content,
+                updated_at,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            category_id,
+            category.name,
+            market_cap_bd,
+            market_cap_change_24h_bd,
+            volume_24h_bd,
+            category.top_3_coins_id.as_deref(),
+            category.top_3_coins.as_deref(),
+            category.content.filter(|c| !c.is_empty()),
+            updated_at,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM categories.category_market_data WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await
+}
+
+// /coins/categories/list を categories.category_list に反映（新しい取得の名前で更新）
+pub async fn store_list(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let list: Vec<CategoryListItem> = snapshot.parse()?;
+
+    for item in list {
+        sqlx::query!(
+            r#"
+            INSERT INTO categories.category_list (
+                category_id,
+                name,
+                fetched_at
+            )
+            VALUES ($1, $2, $3)
+            ON CONFLICT (category_id) DO UPDATE
+            SET name = EXCLUDED.name,
+                fetched_at = EXCLUDED.fetched_at
+            WHERE categories.category_list.fetched_at < EXCLUDED.fetched_at
+            "#,
+            item.category_id,
+            item.name,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// コイン詳細の categories（カテゴリ名）を categories.coin_membership に挿入
+pub async fn insert_membership(
+    conn: &mut PgConnection,
+    coin_id: &str,
+    names: &[String],
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    for name in names {
+        sqlx::query!(
+            r#"
+            INSERT INTO categories.coin_membership (
+                coin_id,
+                category_name,
+                category_id,
+                fetched_at
+            )
+            VALUES (
+                $1,
+                $2,
+                (SELECT category_id FROM categories.category_list WHERE name = $2 LIMIT 1),
+                $3
+            )
+            ON CONFLICT DO NOTHING
+            "#,
+            coin_id,
+            name,
+            fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
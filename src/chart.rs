@@ -0,0 +1,38 @@
+// チャート系エンドポイント（[[ミリ秒のタイムスタンプ, 値], ...]）の共通処理
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use sqlx::types::BigDecimal;
+
+use crate::decimal;
+
+// [[timestamp, value], ...] を (時刻, 値) の列に変換（時刻が解析できない点は除く）
+pub fn points(series: &Value) -> Vec<(DateTime<Utc>, Option<BigDecimal>)> {
+    let Some(items) = series.as_array() else {
+        return Vec::new();
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let ms = item.get(0)?.as_f64()?;
+            let time = DateTime::from_timestamp_millis(ms as i64)?;
+            Some((time, item.get(1).and_then(decimal::from_value)))
+        })
+        .collect()
+}
+
+// from〜to を最大 max_days 日ずつの区間に分割（range 系エンドポイントの期間上限対策）
+pub fn windows(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_days: i64,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut windows = Vec::new();
+    let mut start = from;
+    while start < to {
+        let end = (start + Duration::days(max_days)).min(to);
+        windows.push((start, end));
+        start = end;
+    }
+    windows
+}
@@ -0,0 +1,33 @@
+// 各バイナリで共通のコマンドライン引数の解析
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::Error;
+
+// 日付（2026-01-01）またはRFC3339形式の日時を受け付ける
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>, Error> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+// カンマ区切りの引数を分割（前後の空白と空の要素は除く）
+pub fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect()
+}
+
+// ethereum:0xabc,solana:XYZ 形式の引数を (チェーン, アドレス) の組に分割
+// チェーンは小文字にそろえ、アドレスは大文字小文字を区別するチェーンがあるためそのまま残す
+// what はエラーメッセージに表示する形式（例: "<chain>:<pair_address>"）
+pub fn split_pairs(value: &str, what: &str) -> Result<Vec<(String, String)>, Error> {
+    split_list(value)
+        .into_iter()
+        .map(|entry| match entry.split_once(':') {
+            Some((chain, address)) if !chain.is_empty() && !address.is_empty() => {
+                Ok((chain.to_lowercase(), address.to_string()))
+            }
+            _ => Err(format!("expected {}: {}", what, entry).into()),
+        })
+        .collect()
+}
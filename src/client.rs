@@ -0,0 +1,105 @@
+// API呼び出しと生レスポンスのアーカイブをまとめて行うクライアント
+use chrono::Utc;
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Duration, Instant};
+
+use crate::archive::{self, Snapshot};
+use crate::{Error, COINGECKO_API};
+
+// リクエスト間隔の既定値（CoinGeckoの無料枠のレート制限回避）
+const DEFAULT_INTERVAL_MS: u64 = 1500;
+
+// クローン間で共有されるレート制限（次にリクエストしてよい時刻）
+struct RateLimit {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimit {
+    async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        sleep_until(*next).await;
+        *next = Instant::now() + self.interval;
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    pool: PgPool,
+    api: &'static str,
+    limit: Arc<RateLimit>,
+}
+
+impl Client {
+    // CoinGecko API用のクライアント
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_api(pool, COINGECKO_API)
+    }
+
+    // 任意のベースURL用のクライアント（DEX Screenerなど）
+    // リクエスト間隔は REQUEST_INTERVAL_MS で変更できる
+    pub fn with_api(pool: PgPool, api: &'static str) -> Self {
+        let interval = env::var("REQUEST_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_INTERVAL_MS);
+
+        Self {
+            http: reqwest::Client::new(),
+            pool,
+            api,
+            limit: Arc::new(RateLimit {
+                interval: Duration::from_millis(interval),
+                next: Mutex::new(Instant::now()),
+            }),
+        }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    // endpoint中の {name} はparamsの同名の値で置換し、残りはクエリ文字列にする
+    // 成功したレスポンスだけを raw.responses に保存して返す
+    pub async fn get(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Snapshot, Error> {
+        let mut path = endpoint.to_string();
+        let mut query = Vec::new();
+        let mut recorded = Map::new();
+
+        for &(key, value) in params {
+            let placeholder = format!("{{{}}}", key);
+            if path.contains(&placeholder) {
+                path = path.replace(&placeholder, value);
+            } else {
+                query.push((key, value));
+            }
+            recorded.insert(key.to_string(), Value::String(value.to_string()));
+        }
+
+        let url = format!("{}{}", self.api, path);
+        self.limit.wait().await;
+        let res = self.http.get(&url).query(&query).send().await?;
+        let status = res.status();
+        let text = res.text().await?;
+
+        if !status.is_success() {
+            return Err(format!("{} from {}: {}", status, url, text).into());
+        }
+
+        let snapshot = Snapshot {
+            api: self.api.to_string(),
+            endpoint: endpoint.to_string(),
+            params: Value::Object(recorded),
+            body: serde_json::from_str(&text)?,
+            fetched_at: Utc::now(),
+        };
+        archive::store(&self.pool, &snapshot).await?;
+
+        Ok(snapshot)
+    }
+}
@@ -0,0 +1,418 @@
+// /coins/{id} のレスポンスを coins.detail と関連テーブルに変換する
+use chrono::{DateTime, NaiveDate, Utc};
+use num_traits::FromPrimitive;
+use serde::Deserialize;
+use sqlx::{PgConnection, types::BigDecimal};
+use std::collections::HashMap;
+
+use crate::{archive::Snapshot, categories, Error};
+
+// 一覧の先頭から詳細（/coins/{id}）を取得する件数（APIレート制限対策）
+pub const DETAIL_LIMIT: usize = 100;
+
+// /coins/list?include_platform=true の1件分
+#[derive(Debug, Deserialize)]
+pub struct CoinListItem {
+    pub id: String,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub platforms: HashMap<String, Option<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoinDetail {
+    id: String,
+    symbol: Option<String>,
+    name: Option<String>,
+    hashing_algorithm: Option<String>,
+    description: Option<HashMap<String, Option<String>>>,
+    links: Option<Links>,
+    genesis_date: Option<String>,
+    market_cap_rank: Option<i32>,
+    market_data: Option<MarketData>,
+    categories: Option<Vec<Option<String>>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Links {
+    homepage: Option<Vec<String>>,
+    blockchain_site: Option<Vec<String>>,
+    official_forum_url: Option<Vec<String>>,
+    chat_url: Option<Vec<String>>,
+    announcement_url: Option<Vec<String>>,
+    twitter_screen_name: Option<String>,
+    facebook_username: Option<String>,
+    telegram_channel_identifier: Option<String>,
+    subreddit_url: Option<String>,
+    repos_url: Option<ReposUrl>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReposUrl {
+    github: Option<Vec<String>>,
+    bitbucket: Option<Vec<String>>,
+}
+
+// 空文字を除いたURL一覧（何も残らなければ None）
+fn non_empty(urls: Option<Vec<String>>) -> Option<Vec<String>> {
+    urls.and_then(|vec| {
+        let filtered: Vec<String> = vec.into_iter().filter(|s| !s.is_empty()).collect();
+        if filtered.is_empty() {
+            None
+        } else {
+            Some(filtered)
+        }
+    })
+}
+
+// 説明文のHTMLから危険なタグ・属性を取り除く（リンクなどは残す）
+fn sanitize(html: &str) -> String {
+    ammonia::clean(html)
+}
+
+// 言語ごとの説明文を coins.descriptions に挿入（空の言語は除く）
+async fn insert_descriptions(
+    conn: &mut PgConnection,
+    coin_id: &str,
+    descriptions: &HashMap<String, Option<String>>,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    for (language, text) in descriptions {
+        let Some(text) = text.as_deref().filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO coins.descriptions (
+                coin_id,
+                language,
+                description,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4)
+            "#,
+            coin_id,
+            language,
+            sanitize(text),
+            fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// links を coins.links に挿入
+async fn insert_links(
+    conn: &mut PgConnection,
+    coin_id: &str,
+    links: Links,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    let non_empty_str = |s: Option<String>| s.filter(|s| !s.is_empty());
+    let (repos_github, repos_bitbucket) = match links.repos_url {
+        Some(repos) => (non_empty(repos.github), non_empty(repos.bitbucket)),
+        None => (None, None),
+    };
+    let homepage = non_empty(links.homepage);
+    let blockchain_site = non_empty(links.blockchain_site);
+    let official_forum_url = non_empty(links.official_forum_url);
+    let chat_url = non_empty(links.chat_url);
+    let announcement_url = non_empty(links.announcement_url);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO coins.links (
+            coin_id,
+            homepage,
+            blockchain_site,
+            official_forum_url,
+            chat_url,
+            announcement_url,
+            twitter_screen_name,
+            facebook_username,
+            telegram_channel_identifier,
+            subreddit_url,
+            repos_github,
+            repos_bitbucket,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        "#,
+        coin_id,
+        homepage.as_deref(),
+        blockchain_site.as_deref(),
+        official_forum_url.as_deref(),
+        chat_url.as_deref(),
+        announcement_url.as_deref(),
+        non_empty_str(links.twitter_screen_name),
+        non_empty_str(links.facebook_username),
+        non_empty_str(links.telegram_channel_identifier),
+        non_empty_str(links.subreddit_url),
+        repos_github.as_deref(),
+        repos_bitbucket.as_deref(),
+        fetched_at
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// 通貨コード → 値（通貨によっては null）
+type PerCurrency = Option<HashMap<String, Option<f64>>>;
+
+// market_data ブロック（価格系は通貨ごと、供給量はコイン単位）
+#[derive(Debug, Deserialize)]
+struct MarketData {
+    current_price: PerCurrency,
+    market_cap: PerCurrency,
+    fully_diluted_valuation: PerCurrency,
+    total_volume: PerCurrency,
+    high_24h: PerCurrency,
+    low_24h: PerCurrency,
+    ath: PerCurrency,
+    ath_change_percentage: PerCurrency,
+    ath_date: Option<HashMap<String, Option<String>>>,
+    atl: PerCurrency,
+    atl_change_percentage: PerCurrency,
+    atl_date: Option<HashMap<String, Option<String>>>,
+    price_change_24h_in_currency: PerCurrency,
+    price_change_percentage_1h_in_currency: PerCurrency,
+    price_change_percentage_24h_in_currency: PerCurrency,
+    price_change_percentage_7d_in_currency: PerCurrency,
+    price_change_percentage_14d_in_currency: PerCurrency,
+    price_change_percentage_30d_in_currency: PerCurrency,
+    price_change_percentage_60d_in_currency: PerCurrency,
+    price_change_percentage_200d_in_currency: PerCurrency,
+    price_change_percentage_1y_in_currency: PerCurrency,
+    market_cap_change_24h_in_currency: PerCurrency,
+    market_cap_change_percentage_24h_in_currency: PerCurrency,
+    circulating_supply: Option<f64>,
+    total_supply: Option<f64>,
+    max_supply: Option<f64>,
+    last_updated: Option<String>,
+}
+
+// 通貨ごとの値を BigDecimal に変換
+fn pick(map: &PerCurrency, currency: &str) -> Option<BigDecimal> {
+    map.as_ref()?.get(currency).copied().flatten().and_then(BigDecimal::from_f64)
+}
+
+// RFC3339形式の日時を変換（"2021-11-10T14:24:11.849Z" など）
+fn parse_time(value: Option<&str>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value?).ok().map(|t| t.with_timezone(&Utc))
+}
+
+fn pick_time(map: &Option<HashMap<String, Option<String>>>, currency: &str) -> Option<DateTime<Utc>> {
+    parse_time(map.as_ref()?.get(currency)?.as_deref())
+}
+
+// market_data を current_price に含まれる通貨ごとに coins.market_snapshot へ挿入
+async fn insert_market_snapshot(
+    conn: &mut PgConnection,
+    coin_id: &str,
+    data: &MarketData,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    let Some(currencies) = &data.current_price else {
+        return Ok(());
+    };
+
+    let circulating_supply = data.circulating_supply.and_then(BigDecimal::from_f64);
+    let total_supply = data.total_supply.and_then(BigDecimal::from_f64);
+    let max_supply = data.max_supply.and_then(BigDecimal::from_f64);
+    let last_updated = parse_time(data.last_updated.as_deref());
+
+    for currency in currencies.keys() {
+        let c = currency.as_str();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO coins.market_snapshot (
+                coin_id,
+                vs_currency,
+                current_price,
+                market_cap,
+                fully_diluted_valuation,
+                total_volume,
+                high_24h,
+                low_24h,
+                ath,
+                ath_change_percentage,
+                ath_date,
+                atl,
+                atl_change_percentage,
+                atl_date,
+                price_change_24h,
+                price_change_percentage_1h,
+                price_change_percentage_24h,
+                price_change_percentage_7d,
+                price_change_percentage_14d,
+                price_change_percentage_30d,
+                price_change_percentage_60d,
+                price_change_percentage_200d,
+                price_change_percentage_1y,
+                market_cap_change_24h,
+                market_cap_change_percentage_24h,
+                circulating_supply,
+                total_supply,
+                max_supply,
+                last_updated,
+                fetched_at
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
+                $21, $22, $23, $24, $25, $26, $27, $28, $29, $30
+            )
+            "#,
+            coin_id,
+            c,
+            pick(&data.current_price, c),
+            pick(&data.market_cap, c),
+            pick(&data.fully_diluted_valuation, c),
+            pick(&data.total_volume, c),
+            pick(&data.high_24h, c),
+            pick(&data.low_24h, c),
+            pick(&data.ath, c),
+            pick(&data.ath_change_percentage, c),
+            pick_time(&data.ath_date, c),
+            pick(&data.atl, c),
+            pick(&data.atl_change_percentage, c),
+            pick_time(&data.atl_date, c),
+            pick(&data.price_change_24h_in_currency, c),
+            pick(&data.price_change_percentage_1h_in_currency, c),
+            pick(&data.price_change_percentage_24h_in_currency, c),
+            pick(&data.price_change_percentage_7d_in_currency, c),
+            pick(&data.price_change_percentage_14d_in_currency, c),
+            pick(&data.price_change_percentage_30d_in_currency, c),
+            pick(&data.price_change_percentage_60d_in_currency, c),
+            pick(&data.price_change_percentage_200d_in_currency, c),
+            pick(&data.price_change_percentage_1y_in_currency, c),
+            pick(&data.market_cap_change_24h_in_currency, c),
+            pick(&data.market_cap_change_percentage_24h_in_currency, c),
+            circulating_supply,
+            total_supply,
+            max_supply,
+            last_updated,
+            fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn insert_detail(
+    conn: &mut PgConnection,
+    detail: CoinDetail,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    if let Some(market_data) = &detail.market_data {
+        insert_market_snapshot(conn, &detail.id, market_data, fetched_at).await?;
+    }
+
+    // 所属カテゴリ（null や空文字は除く）
+    let category_names: Vec<String> = detail.categories
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|name| !name.is_empty())
+        .cloned()
+        .collect();
+    categories::insert_membership(conn, &detail.id, &category_names, fetched_at).await?;
+
+    if let Some(descriptions) = &detail.description {
+        insert_descriptions(conn, &detail.id, descriptions, fetched_at).await?;
+    }
+
+    let links = detail.links.unwrap_or_default();
+    let homepage = non_empty(links.homepage.clone());
+    insert_links(conn, &detail.id, links, fetched_at).await?;
+
+    // coins.detail には英語の説明文を残す
+    let description = detail.description
+        .as_ref()
+        .and_then(|d| d.get("en").cloned().flatten())
+        .filter(|t| !t.is_empty())
+        .map(|t| sanitize(&t));
+    let genesis_date = match detail.genesis_date {
+        Some(date_str) => NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok(),
+        None => None,
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO coins.detail (
+            id,
+            symbol,
+            name,
+            hashing_algorithm,
+            description,
+            homepage,
+            genesis_date,
+            market_cap_rank,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        detail.id,
+        detail.symbol,
+        detail.name,
+        detail.hashing_algorithm,
+        description,
+        homepage.as_deref(),
+        genesis_date,
+        detail.market_cap_rank,
+        fetched_at
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから coins.detail と関連テーブルの該当行を作り直す
+pub async fn rebuild_detail(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM coins.detail WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM coins.market_snapshot WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM coins.descriptions WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM coins.links WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM categories.coin_membership WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    insert_detail(conn, snapshot.parse()?, snapshot.fetched_at).await
+}
@@ -0,0 +1,125 @@
+// /companies/public_treasury/{coin_id} のレスポンスを companies.public_holdings と
+// companies.treasury_totals に変換する
+use num_traits::FromPrimitive;
+use serde::Deserialize;
+use sqlx::{PgConnection, types::BigDecimal};
+
+use crate::{archive::Snapshot, Error};
+
+// 既定で取得する保有コイン
+pub const DEFAULT_COINS: [&str; 2] = ["bitcoin", "ethereum"];
+
+#[derive(Debug, Deserialize)]
+struct Company {
+    name: String,
+    symbol: Option<String>,
+    country: Option<String>,
+    total_holdings: Option<f64>,
+    total_entry_value_usd: Option<f64>,
+    total_value_usd: Option<f64>,
+    percentage_of_supply: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiResponse {
+    total_holdings: Option<f64>,
+    total_value_usd: Option<f64>,
+    market_cap_dominance: Option<f64>,
+    companies: Vec<Company>,
+}
+
+// 推移を追うための企業キー（"NASDAQ:MSTR" などの銘柄コード、無ければ企業名）
+fn company_key(company: &Company) -> String {
+    match company.symbol.as_deref().map(str::trim) {
+        Some(symbol) if !symbol.is_empty() => symbol.to_uppercase(),
+        _ => format!("name:{}", company.name.trim().to_lowercase()),
+    }
+}
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let coin_id = snapshot.param("coin_id").ok_or("coin_id is missing")?;
+    let response = snapshot.parse::<ApiResponse>()?;
+
+    // 合計値を保存
+    sqlx::query!(
+        r#"
+        INSERT INTO companies.treasury_totals (
+            coin_id,
+            total_holdings,
+            total_value_usd,
+            market_cap_dominance,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        coin_id,
+        response.total_holdings.and_then(BigDecimal::from_f64),
+        response.total_value_usd.and_then(BigDecimal::from_f64),
+        response.market_cap_dominance.and_then(BigDecimal::from_f64),
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // 各企業情報をDBに挿入
+    for company in response.companies {
+        println!("📥 Inserting company: {:?}", company.name);
+
+        let key = company_key(&company);
+        let holdings_bd = company.total_holdings.and_then(BigDecimal::from_f64);
+        let entry_value_bd = company.total_entry_value_usd.and_then(BigDecimal::from_f64);
+        let value_bd = company.total_value_usd.and_then(BigDecimal::from_f64);
+        let percent_bd = company.percentage_of_supply.and_then(BigDecimal::from_f64);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO companies.public_holdings (
+                coin_id,
+                company_key,
+                company_name,
+                symbol,
+                country,
+                total_holdings,
+                total_entry_value_usd,
+                total_value_usd,
+                percentage_of_supply,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            coin_id,
+            key,
+            company.name,
+            company.symbol,
+            company.country,
+            holdings_bd,
+            entry_value_bd,
+            value_bd,
+            percent_bd,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM companies.public_holdings WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM companies.treasury_totals WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await
+}
@@ -0,0 +1,154 @@
+// コインのプラットフォーム別コントラクトを contract.token_info に変換する
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgConnection;
+use std::collections::HashMap;
+
+use crate::coins::{CoinListItem, DETAIL_LIMIT};
+use crate::{archive::Snapshot, Error};
+
+// コイン詳細APIのレスポンスで使用（必要な情報のみ保持）
+#[derive(Debug, Deserialize)]
+pub struct CoinDetail {
+    id: String,
+    name: Option<String>,
+    symbol: Option<String>,
+    #[serde(default)]
+    detail_platforms: HashMap<String, DetailPlatform>,
+}
+
+// detail_platforms の1チェーン分（decimalsはチェーンごとに異なりうる）
+#[derive(Debug, Deserialize)]
+struct DetailPlatform {
+    decimal_place: Option<i32>,
+    contract_address: Option<String>,
+}
+
+// プラットフォームごとのコントラクトアドレスを挿入
+async fn insert_platforms<'a>(
+    conn: &mut PgConnection,
+    name: Option<&str>,
+    symbol: Option<&str>,
+    platforms: impl Iterator<Item = (&'a String, &'a str, Option<i32>)>,
+    decimals_differ: Option<bool>,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    for (platform, address, decimals) in platforms {
+        // コントラクトアドレスが空文字ならスキップ
+        if address.is_empty() {
+            continue;
+        }
+
+        // 挿入ログを表示
+        println!("📥 Inserting contract: {} on {}", address, platform);
+
+        // PostgreSQL にコントラクト情報をINSERT（timestampは取得時刻）
+        sqlx::query!(
+            r#"
+            INSERT INTO contract.token_info (
+                platform,
+                contract_address,
+                name,
+                symbol,
+                decimals,
+                decimals_differ,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            Some(platform),
+            address,
+            name,
+            symbol,
+            decimals,
+            decimals_differ,
+            fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// /coins/{id} の詳細から挿入（decimalsは detail_platforms のチェーン別の値）
+pub async fn insert_token_info(
+    conn: &mut PgConnection,
+    detail: &CoinDetail,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    let platforms: Vec<_> = detail.detail_platforms
+        .iter()
+        .filter_map(|(platform, info)| {
+            Some((platform, info.contract_address.as_deref()?, info.decimal_place))
+        })
+        .filter(|(_, address, _)| !address.is_empty())
+        .collect();
+
+    // チェーン間で decimals が食い違うトークンはフラグを立てて警告
+    let mut decimals: Vec<i32> = platforms.iter().filter_map(|(_, _, d)| *d).collect();
+    decimals.sort_unstable();
+    decimals.dedup();
+    let decimals_differ = decimals.len() > 1;
+    if decimals_differ {
+        println!("⚠️ Decimals differ across chains for {}: {:?}", detail.id, decimals);
+    }
+
+    insert_platforms(
+        conn,
+        detail.name.as_deref(),
+        detail.symbol.as_deref(),
+        platforms.into_iter(),
+        Some(decimals_differ),
+        fetched_at,
+    )
+    .await
+}
+
+// /coins/list?include_platform=true の1件から挿入（decimalsは一覧に含まれない）
+pub async fn insert_listed(
+    conn: &mut PgConnection,
+    coin: &CoinListItem,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    insert_platforms(
+        conn,
+        coin.name.as_deref(),
+        coin.symbol.as_deref(),
+        coin.platforms
+            .iter()
+            .filter_map(|(platform, address)| Some((platform, address.as_deref()?, None))),
+        None,
+        fetched_at,
+    )
+    .await
+}
+
+// アーカイブ済みのレスポンスから contract.token_info の該当行を作り直す
+pub async fn rebuild_token_info(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM contract.token_info WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    insert_token_info(conn, &snapshot.parse()?, snapshot.fetched_at).await
+}
+
+// 一覧のスナップショットから、詳細を取得しなかったコインの行を作り直す
+pub async fn rebuild_listed(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM contract.token_info WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let coin_list: Vec<CoinListItem> = snapshot.parse()?;
+    for coin in coin_list.iter().skip(DETAIL_LIMIT) {
+        insert_listed(conn, coin, snapshot.fetched_at).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+// 文字列・数値のどちらで返ってくる値も BigDecimal として受け取る
+// 文字列はそのまま解析するので桁落ちしない
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use sqlx::types::BigDecimal;
+use std::str::FromStr;
+
+// JSONの値を BigDecimal に変換（null・空文字・解析できない値は None）
+pub fn from_value(value: &Value) -> Option<BigDecimal> {
+    match value {
+        Value::String(s) => BigDecimal::from_str(s.trim()).ok(),
+        Value::Number(n) => BigDecimal::from_str(&n.to_string()).ok(),
+        _ => None,
+    }
+}
+
+// #[serde(default, deserialize_with = "decimal::lenient")] で使う
+pub fn lenient<'de, D>(deserializer: D) -> Result<Option<BigDecimal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(from_value))
+}
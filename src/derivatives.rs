@@ -0,0 +1,425 @@
+// /derivatives と /derivatives/exchanges のレスポンスを derivatives スキーマに変換する
+use chrono::{DateTime, Utc};
+use num_traits::FromPrimitive;
+use serde::Deserialize;
+use sqlx::{PgConnection, types::BigDecimal};
+
+use crate::{archive::Snapshot, decimal, Error};
+
+// /derivatives の1件分（id は返らないため market + symbol + contract_type で識別する）
+#[derive(Debug, Deserialize)]
+pub struct DerivativeMarket {
+    market: Option<String>,
+    symbol: Option<String>,
+    index_id: Option<String>,
+    price: Option<String>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    price_percentage_change_24h: Option<BigDecimal>,
+    contract_type: Option<String>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    index: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    basis: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    spread: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    funding_rate: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    open_interest: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    volume_24h: Option<BigDecimal>,
+    last_traded_at: Option<i64>,
+    expired_at: Option<i64>,
+}
+
+// /derivatives/exchanges の1件分
+#[derive(Debug, Deserialize)]
+struct DerivativeExchange {
+    id: String,
+    name: Option<String>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    open_interest_btc: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    trade_volume_24h_btc: Option<BigDecimal>,
+    number_of_perpetual_pairs: Option<i32>,
+    number_of_futures_pairs: Option<i32>,
+    year_established: Option<i32>,
+    country: Option<String>,
+    url: Option<String>,
+}
+
+// /derivatives/exchanges/{id}?include_tickers=all
+#[derive(Debug, Deserialize)]
+struct DerivativeExchangeDetail {
+    #[serde(default)]
+    tickers: Vec<ExchangeTicker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangeTicker {
+    symbol: Option<String>,
+    base: Option<String>,
+    target: Option<String>,
+    contract_type: Option<String>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    last: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    h24_percentage_change: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    index: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    index_basis_percentage: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    bid_ask_spread: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    funding_rate: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    open_interest_usd: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    h24_volume: Option<BigDecimal>,
+    converted_volume: Option<ConvertedValue>,
+    last_traded: Option<i64>,
+    expired_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConvertedValue {
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    usd: Option<BigDecimal>,
+}
+
+// 1回の取り込みで保存・スキップした件数
+#[derive(Debug, Default)]
+pub struct StoreCount {
+    pub stored: usize,
+    pub skipped: usize,
+}
+
+// derivatives.funding_intervals に間隔が無い場合に仮定する資金調達の間隔（時間）
+pub const DEFAULT_FUNDING_INTERVAL_HOURS: i32 = 8;
+
+// 1回あたりの資金調達率（%）を、資金調達の間隔（時間）から年率（%）に換算
+pub fn annualize_funding(rate: &BigDecimal, interval_hours: i32) -> BigDecimal {
+    rate * BigDecimal::from(365 * 24) / BigDecimal::from(interval_hours)
+}
+
+// 年率換算したベーシスの小数点以下の桁数
+const ANNUALIZED_BASIS_SCALE: i64 = 8;
+
+// 期日付き先物のベーシス（%）を満期までの残り期間で年率（%）に換算
+pub fn annualize_basis(
+    basis: &BigDecimal,
+    expired_at: DateTime<Utc>,
+    fetched_at: DateTime<Utc>,
+) -> Option<BigDecimal> {
+    let remaining = (expired_at - fetched_at).num_seconds();
+    if remaining <= 0 {
+        return None;
+    }
+    let annualized = basis * BigDecimal::from(365 * 24 * 60 * 60) / BigDecimal::from(remaining);
+    Some(annualized.round(ANNUALIZED_BASIS_SCALE))
+}
+
+// UNIX秒 → 日時
+fn from_unix(secs: Option<i64>) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(secs?, 0)
+}
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<StoreCount, Error> {
+    let response = snapshot.parse::<Vec<DerivativeMarket>>()?;
+    let mut count = StoreCount::default();
+
+    for market in response {
+        println!("{:?}", market); // ← デバッグ出力で中身を確認
+
+        match (&market.market, &market.symbol) {
+            (Some(market_name), Some(symbol)) => {
+                // price: Option<String> → Option<f64> → Option<BigDecimal>
+                let price_bd = market
+                    .price
+                    .as_ref()
+                    .and_then(|p| p.parse::<f64>().ok())
+                    .and_then(BigDecimal::from_f64);
+
+                let expired_at = from_unix(market.expired_at);
+                let annualized_basis = match (&market.basis, expired_at) {
+                    (Some(basis), Some(expired_at)) => annualize_basis(basis, expired_at, snapshot.fetched_at),
+                    _ => None,
+                };
+
+                let result = sqlx::query!(
+                    r#"
+                    INSERT INTO derivatives.derivative_markets (
+                        market,
+                        symbol,
+                        index,
+                        price,
+                        price_percentage_change_24h,
+                        contract_type,
+                        index_price,
+                        basis,
+                        spread,
+                        funding_rate,
+                        open_interest,
+                        volume_24h,
+                        last_traded_at,
+                        expired_at,
+                        annualized_basis,
+                        fetched_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                    ON CONFLICT (market, symbol, (coalesce(contract_type, '')), fetched_at) DO NOTHING
+                    "#,
+                    market_name,
+                    symbol,
+                    market.index_id,
+                    price_bd,
+                    market.price_percentage_change_24h,
+                    market.contract_type,
+                    market.index,
+                    market.basis,
+                    market.spread,
+                    market.funding_rate,
+                    market.open_interest,
+                    market.volume_24h,
+                    from_unix(market.last_traded_at),
+                    expired_at,
+                    annualized_basis,
+                    snapshot.fetched_at
+                )
+                .execute(&mut *conn)
+                .await?;
+
+                // 無期限先物は資金調達率の時系列にも保存
+                if market.contract_type.as_deref() == Some("perpetual")
+                    && let Some(funding_rate) = &market.funding_rate
+                {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO derivatives.funding_rates (
+                            market,
+                            symbol,
+                            funding_rate,
+                            basis,
+                            price,
+                            index_price,
+                            open_interest,
+                            fetched_at
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                        ON CONFLICT DO NOTHING
+                        "#,
+                        market_name,
+                        symbol,
+                        funding_rate,
+                        market.basis,
+                        price_bd,
+                        market.index,
+                        market.open_interest,
+                        snapshot.fetched_at
+                    )
+                    .execute(&mut *conn)
+                    .await?;
+                }
+
+                // 同じキーが同じレスポンスに重複していた場合は1件だけ残る
+                if result.rows_affected() == 1 {
+                    count.stored += 1;
+                } else {
+                    println!("⚠️ Skipped a duplicate contract: {} {}", market_name, symbol);
+                    count.skipped += 1;
+                }
+            }
+            _ => {
+                // market か symbol がない場合はスキップ（ログは残す）
+                println!("⚠️ Skipped a contract because market or symbol was missing.");
+                count.skipped += 1;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM derivatives.derivative_markets WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM derivatives.funding_rates WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await?;
+    Ok(())
+}
+
+// /derivatives/exchanges の1ページ分を derivatives.exchanges に挿入し、取引所IDを返す
+pub async fn store_exchanges(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<Vec<String>, Error> {
+    let exchanges: Vec<DerivativeExchange> = snapshot.parse()?;
+    let mut ids = Vec::new();
+
+    for exchange in exchanges {
+        println!("📥 Inserting derivatives exchange: {:?}", exchange.id);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO derivatives.exchanges (
+                id,
+                name,
+                open_interest_btc,
+                trade_volume_24h_btc,
+                number_of_perpetual_pairs,
+                number_of_futures_pairs,
+                year_established,
+                country,
+                url,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            exchange.id,
+            exchange.name,
+            exchange.open_interest_btc,
+            exchange.trade_volume_24h_btc,
+            exchange.number_of_perpetual_pairs,
+            exchange.number_of_futures_pairs,
+            exchange.year_established,
+            exchange.country,
+            exchange.url,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        ids.push(exchange.id);
+    }
+
+    Ok(ids)
+}
+
+pub async fn rebuild_exchanges(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM derivatives.exchanges WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store_exchanges(conn, snapshot).await?;
+    Ok(())
+}
+
+// /derivatives/exchanges/{id} のティッカーを derivatives.exchange_tickers に挿入
+pub async fn store_exchange_tickers(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let exchange_id = snapshot.param("id").ok_or("id is missing")?;
+    let detail: DerivativeExchangeDetail = snapshot.parse()?;
+
+    for ticker in detail.tickers {
+        let Some(symbol) = &ticker.symbol else {
+            println!("⚠️ Skipped a ticker on {} because symbol was missing.", exchange_id);
+            continue;
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO derivatives.exchange_tickers (
+                exchange_id,
+                symbol,
+                base,
+                target,
+                contract_type,
+                last,
+                price_change_24h,
+                index_price,
+                basis,
+                spread,
+                funding_rate,
+                open_interest_usd,
+                volume_24h,
+                volume_24h_usd,
+                last_traded_at,
+                expired_at,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            "#,
+            exchange_id,
+            symbol,
+            ticker.base,
+            ticker.target,
+            ticker.contract_type,
+            ticker.last,
+            ticker.h24_percentage_change,
+            ticker.index,
+            ticker.index_basis_percentage,
+            ticker.bid_ask_spread,
+            ticker.funding_rate,
+            ticker.open_interest_usd,
+            ticker.h24_volume,
+            ticker.converted_volume.and_then(|v| v.usd),
+            from_unix(ticker.last_traded),
+            from_unix(ticker.expired_at),
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn rebuild_exchange_tickers(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM derivatives.exchange_tickers WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store_exchange_tickers(conn, snapshot).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn annualize_funding_every_8_hours() {
+        // 0.01% × 3回/日 × 365日
+        assert_eq!(annualize_funding(&dec("0.01"), 8), dec("10.95"));
+    }
+
+    #[test]
+    fn annualize_funding_hourly() {
+        assert_eq!(annualize_funding(&dec("0.01"), 1), dec("87.6"));
+    }
+
+    #[test]
+    fn annualize_basis_expired_contract() {
+        let fetched_at = Utc::now();
+        assert_eq!(annualize_basis(&dec("1"), fetched_at, fetched_at), None);
+        assert_eq!(annualize_basis(&dec("1"), fetched_at - Duration::days(1), fetched_at), None);
+    }
+
+    #[test]
+    fn annualize_basis_30_days_before_expiry() {
+        let fetched_at = Utc::now();
+        let expired_at = fetched_at + Duration::days(30);
+        // 1% × 365 / 30
+        assert_eq!(annualize_basis(&dec("1"), expired_at, fetched_at), Some(dec("12.16666667")));
+    }
+}
@@ -0,0 +1,271 @@
+// /exchanges のレスポンスを exchanges.exchange_info に、
+// /exchanges/{id}/tickers のレスポンスを exchanges.tickers に変換する
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::{PgConnection, types::BigDecimal};
+
+use crate::{archive::Snapshot, chart, decimal, Error};
+
+// /exchanges の1ページあたりの件数（APIの上限）
+pub const PER_PAGE: usize = 250;
+
+// /exchanges/{id}/tickers の1ページあたりの件数（APIの固定値）
+pub const TICKERS_PER_PAGE: usize = 100;
+
+// /exchanges/{id}/volume_chart/range で1回に指定できる期間（日）
+pub const VOLUME_RANGE_MAX_DAYS: i64 = 31;
+
+#[derive(Debug, Deserialize)]
+pub struct Exchange {
+    id: String,
+    name: Option<String>,
+    year_established: Option<i32>,
+    country: Option<String>,
+    description: Option<String>,
+    url: Option<String>,
+    image: Option<String>,
+    has_trading_incentive: Option<bool>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    trade_volume_24h_btc: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    trade_volume_24h_btc_normalized: Option<BigDecimal>,
+    trust_score: Option<i32>,
+    trust_score_rank: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangeTickers {
+    #[serde(default)]
+    tickers: Vec<Ticker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Ticker {
+    base: Option<String>,
+    target: Option<String>,
+    coin_id: Option<String>,
+    target_coin_id: Option<String>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    last: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    volume: Option<BigDecimal>,
+    converted_last: Option<ConvertedValue>,
+    converted_volume: Option<ConvertedValue>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    bid_ask_spread_percentage: Option<BigDecimal>,
+    trust_score: Option<String>,
+    is_stale: Option<bool>,
+    is_anomaly: Option<bool>,
+    last_traded_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConvertedValue {
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    usd: Option<BigDecimal>,
+}
+
+// 取引所一覧を挿入し、取得した取引所IDを順番通りに返す
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<Vec<String>, Error> {
+    let exchanges: Vec<Exchange> = snapshot.parse()?;
+    let mut ids = Vec::new();
+
+    // 各取引所をDBに挿入
+    for exchange in exchanges {
+        println!("📥 Inserting Exchange: {:?}", exchange.id);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO exchanges.exchange_info (
+                id,
+                name,
+                year_established,
+                country,
+                trade_volume_24h_btc,
+                trust_score,
+                url,
+                image,
+                description,
+                has_trading_incentive,
+                trust_score_rank,
+                trade_volume_24h_btc_normalized,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+            exchange.id,
+            exchange.name,
+            exchange.year_established,
+            exchange.country,
+            exchange.trade_volume_24h_btc,
+            exchange.trust_score,
+            exchange.url,
+            exchange.image,
+            exchange.description.filter(|d| !d.is_empty()),
+            exchange.has_trading_incentive,
+            exchange.trust_score_rank,
+            exchange.trade_volume_24h_btc_normalized,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        ids.push(exchange.id);
+    }
+
+    Ok(ids)
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM exchanges.exchange_info WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await?;
+    Ok(())
+}
+
+// /exchanges/{id}/tickers の1ページ分を挿入し、レスポンスに含まれていた件数を返す
+// （件数が TICKERS_PER_PAGE 未満なら最終ページ）
+pub async fn store_tickers(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<usize, Error> {
+    let exchange_id = snapshot.param("id").ok_or("id is missing")?;
+    let page: ExchangeTickers = snapshot.parse()?;
+
+    for ticker in &page.tickers {
+        let (Some(base), Some(target)) = (&ticker.base, &ticker.target) else {
+            println!("⚠️ Skipped a ticker on {} because base or target was missing.", exchange_id);
+            continue;
+        };
+
+        // 最終取引時刻はISO 8601の文字列
+        let last_traded_at = ticker.last_traded_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+
+        sqlx::query!(
+            r#"
+            INSERT INTO exchanges.tickers (
+                exchange_id,
+                base,
+                target,
+                coin_id,
+                target_coin_id,
+                last,
+                volume,
+                converted_last_usd,
+                converted_volume_usd,
+                bid_ask_spread_percentage,
+                trust_score,
+                is_stale,
+                is_anomaly,
+                last_traded_at,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#,
+            exchange_id,
+            base,
+            target,
+            ticker.coin_id,
+            ticker.target_coin_id,
+            ticker.last,
+            ticker.volume,
+            ticker.converted_last.as_ref().and_then(|v| v.usd.clone()),
+            ticker.converted_volume.as_ref().and_then(|v| v.usd.clone()),
+            ticker.bid_ask_spread_percentage,
+            ticker.trust_score,
+            ticker.is_stale,
+            ticker.is_anomaly,
+            last_traded_at,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(page.tickers.len())
+}
+
+pub async fn rebuild_tickers(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM exchanges.tickers WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store_tickers(conn, snapshot).await?;
+    Ok(())
+}
+
+// 全ページを取得し終えた一覧を記録し、前回の一覧から消えた取引所を返す
+pub async fn record_list_run(
+    conn: &mut PgConnection,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    exchange_count: usize,
+) -> Result<Vec<String>, Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO exchanges.list_runs (started_at, finished_at, exchange_count)
+        VALUES ($1, $2, $3)
+        "#,
+        started_at,
+        finished_at,
+        exchange_count as i32
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let disappeared = sqlx::query_scalar!(
+        r#"
+        SELECT exchange_id AS "exchange_id!"
+        FROM exchanges.disappeared_exchanges
+        WHERE missing_since = $1
+        ORDER BY exchange_id
+        "#,
+        started_at
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(disappeared)
+}
+
+// /exchanges/{id}/volume_chart(/range) の出来高を exchanges.volume_chart に反映し、点の数を返す
+// 同じ時刻の点は、より新しい（または同じ）取得時刻のときだけ上書きする
+pub async fn store_volume_chart(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<usize, Error> {
+    let exchange_id = snapshot.param("id").ok_or("id is missing")?;
+    let points = chart::points(&snapshot.body);
+
+    for (timestamp, volume_btc) in &points {
+        sqlx::query!(
+            r#"
+            INSERT INTO exchanges.volume_chart (
+                exchange_id,
+                timestamp,
+                volume_btc,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (exchange_id, timestamp) DO UPDATE
+            SET volume_btc = EXCLUDED.volume_btc,
+                fetched_at = EXCLUDED.fetched_at
+            WHERE exchanges.volume_chart.fetched_at <= EXCLUDED.fetched_at
+            "#,
+            exchange_id,
+            timestamp,
+            volume_btc.as_ref(),
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(points.len())
+}
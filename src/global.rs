@@ -0,0 +1,358 @@
+// /global のレスポンスを global.market_stats に、
+// /global/decentralized_finance_defi のレスポンスを global.defi_stats に変換する
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::{PgConnection, types::BigDecimal};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{archive::Snapshot, chart, decimal, Error};
+
+// CoinGecko APIのglobalエンドポイントのデータ構造に対応
+#[derive(Debug, Deserialize)]
+struct GlobalData {
+    active_cryptocurrencies: Option<i32>,
+    upcoming_icos: Option<i32>,
+    ongoing_icos: Option<i32>,
+    ended_icos: Option<i32>,
+    markets: Option<i32>,
+    #[serde(default)]
+    total_market_cap: HashMap<String, Value>,
+    #[serde(default)]
+    total_volume: HashMap<String, Value>,
+    #[serde(default)]
+    market_cap_percentage: HashMap<String, Value>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    market_cap_change_percentage_24h_usd: Option<BigDecimal>,
+    updated_at: Option<i64>,
+}
+
+// 通貨・コインごとのマップから値を取り出す（数値は桁落ちさせない）
+fn pick(map: &HashMap<String, Value>, key: &str) -> Option<BigDecimal> {
+    map.get(key).and_then(decimal::from_value)
+}
+
+// APIレスポンス全体構造
+#[derive(Debug, Deserialize)]
+pub struct ApiResponse {
+    data: GlobalData,
+}
+
+// /global/decentralized_finance_defi のデータ（数値は文字列で返る）
+#[derive(Debug, Deserialize)]
+struct DefiData {
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    defi_market_cap: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    eth_market_cap: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    defi_to_eth_ratio: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    trading_volume_24h: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    defi_dominance: Option<BigDecimal>,
+    top_coin_name: Option<String>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    top_coin_defi_dominance: Option<BigDecimal>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DefiResponse {
+    data: DefiData,
+}
+
+// /global/market_cap_chart のレスポンス
+#[derive(Debug, Deserialize)]
+struct MarketCapChartResponse {
+    market_cap_chart: MarketCapChart,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketCapChart {
+    #[serde(default)]
+    market_cap: Value,
+    #[serde(default)]
+    volume: Value,
+}
+
+// 日次系列の1日分
+#[derive(Debug, Default)]
+struct DailyPoint {
+    time: Option<DateTime<Utc>>,
+    market_cap: Option<BigDecimal>,
+    volume: Option<BigDecimal>,
+}
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let response: ApiResponse = snapshot.parse()?;
+
+    // 仮想通貨全体の市場統計情報を取得
+    let g = response.data;
+
+    let total_market_cap_usd = pick(&g.total_market_cap, "usd");
+    let total_volume_usd = pick(&g.total_volume, "usd");
+    let btc_dominance = pick(&g.market_cap_percentage, "btc");
+    let eth_dominance = pick(&g.market_cap_percentage, "eth");
+    let updated_at = g.updated_at.and_then(|t| DateTime::from_timestamp(t, 0));
+
+    // global.market_statsテーブルにデータを挿入
+    sqlx::query!(
+        r#"
+        INSERT INTO global.market_stats (
+            active_cryptocurrencies,
+            upcoming_icos,
+            ongoing_icos,
+            ended_icos,
+            markets,
+            total_market_cap_usd,
+            total_volume_usd,
+            btc_dominance,
+            eth_dominance,
+            market_cap_change_percentage_24h_usd,
+            updated_at,
+            fetched_at
+        )
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+        "#,
+        g.active_cryptocurrencies,
+        g.upcoming_icos,
+        g.ongoing_icos,
+        g.ended_icos,
+        g.markets,
+        total_market_cap_usd,
+        total_volume_usd,
+        btc_dominance,
+        eth_dominance,
+        g.market_cap_change_percentage_24h_usd,
+        updated_at,
+        snapshot.fetched_at,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // 時価総額・出来高のどちらかにある通貨はすべて1行にまとめて保存
+    let currencies: BTreeSet<&String> = g.total_market_cap.keys().chain(g.total_volume.keys()).collect();
+    for currency in currencies {
+        sqlx::query!(
+            r#"
+            INSERT INTO global.currency_totals (
+                vs_currency,
+                total_market_cap,
+                total_volume,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4)
+            "#,
+            currency,
+            pick(&g.total_market_cap, currency),
+            pick(&g.total_volume, currency),
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    // 市場占有率はレスポンスに含まれる全コイン分
+    for (coin, percentage) in &g.market_cap_percentage {
+        sqlx::query!(
+            r#"
+            INSERT INTO global.dominance (
+                coin,
+                market_cap_percentage,
+                fetched_at
+            )
+            VALUES ($1, $2, $3)
+            "#,
+            coin,
+            decimal::from_value(percentage),
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM global.market_stats WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM global.currency_totals WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM global.dominance WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await
+}
+
+// /global/decentralized_finance_defi を global.defi_stats に挿入
+pub async fn store_defi(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let response: DefiResponse = snapshot.parse()?;
+    let d = response.data;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO global.defi_stats (
+            defi_market_cap,
+            eth_market_cap,
+            defi_to_eth_ratio,
+            trading_volume_24h,
+            defi_dominance,
+            top_coin_name,
+            top_coin_defi_dominance,
+            fetched_at
+        )
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+        "#,
+        d.defi_market_cap,
+        d.eth_market_cap,
+        d.defi_to_eth_ratio,
+        d.trading_volume_24h,
+        d.defi_dominance,
+        d.top_coin_name,
+        d.top_coin_defi_dominance,
+        snapshot.fetched_at,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn rebuild_defi(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM global.defi_stats WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store_defi(conn, snapshot).await
+}
+
+// 日次系列の1日分を反映（同じ日は、より新しい取得時刻のときだけ上書き）
+async fn upsert_daily(
+    conn: &mut PgConnection,
+    vs_currency: &str,
+    day: NaiveDate,
+    point: &DailyPoint,
+    source: &str,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO global.daily_market_cap (
+            vs_currency,
+            day,
+            total_market_cap,
+            total_volume,
+            source,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (vs_currency, day) DO UPDATE
+        SET total_market_cap = EXCLUDED.total_market_cap,
+            total_volume = EXCLUDED.total_volume,
+            source = EXCLUDED.source,
+            fetched_at = EXCLUDED.fetched_at
+        WHERE global.daily_market_cap.fetched_at <= EXCLUDED.fetched_at
+        "#,
+        vs_currency,
+        day,
+        point.market_cap,
+        point.volume,
+        source,
+        fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// /global/market_cap_chart の点を日ごとに1つ（その日の最後の点）にまとめて反映し、日数を返す
+pub async fn store_market_cap_chart(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<usize, Error> {
+    let vs_currency = snapshot.param("vs_currency").unwrap_or("usd");
+    let response: MarketCapChartResponse = snapshot.parse()?;
+
+    let mut days: BTreeMap<NaiveDate, DailyPoint> = BTreeMap::new();
+    for (time, market_cap) in chart::points(&response.market_cap_chart.market_cap) {
+        let point = days.entry(time.date_naive()).or_default();
+        if point.time.is_none_or(|t| t <= time) {
+            point.time = Some(time);
+            point.market_cap = market_cap;
+        }
+    }
+    // 出来高は同じ日の最後の点を採用（時価総額と時刻がずれていても日単位で揃える）
+    let mut volume_times: HashMap<NaiveDate, DateTime<Utc>> = HashMap::new();
+    for (time, volume) in chart::points(&response.market_cap_chart.volume) {
+        let day = time.date_naive();
+        if volume_times.get(&day).is_none_or(|t| *t <= time) {
+            volume_times.insert(day, time);
+            days.entry(day).or_default().volume = volume;
+        }
+    }
+
+    for (day, point) in &days {
+        upsert_daily(conn, vs_currency, *day, point, "chart", snapshot.fetched_at).await?;
+    }
+
+    Ok(days.len())
+}
+
+// 保存済みの /global のスナップショットから日次系列を再構成し、日数を返す
+// （currency_totals が無い古いスナップショットは market_stats のUSDの値を使う）
+pub async fn rebuild_daily_from_snapshots(conn: &mut PgConnection, vs_currency: &str) -> Result<usize, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT DISTINCT ON ((fetched_at AT TIME ZONE 'UTC')::date)
+            (fetched_at AT TIME ZONE 'UTC')::date AS "day!",
+            total_market_cap,
+            total_volume,
+            fetched_at AS "fetched_at!"
+        FROM (
+            SELECT total_market_cap, total_volume, fetched_at
+            FROM global.currency_totals
+            WHERE vs_currency = $1
+            UNION ALL
+            SELECT m.total_market_cap_usd, m.total_volume_usd, m.fetched_at
+            FROM global.market_stats m
+            WHERE $1 = 'usd'
+              AND m.fetched_at IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM global.currency_totals c WHERE c.fetched_at = m.fetched_at
+              )
+        ) totals
+        ORDER BY (fetched_at AT TIME ZONE 'UTC')::date, fetched_at DESC
+        "#,
+        vs_currency
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for row in &rows {
+        let point = DailyPoint {
+            time: Some(row.fetched_at),
+            market_cap: row.total_market_cap.clone(),
+            volume: row.total_volume.clone(),
+        };
+        upsert_daily(conn, vs_currency, row.day, &point, "snapshot", row.fetched_at).await?;
+    }
+
+    Ok(rows.len())
+}
@@ -0,0 +1,30 @@
+// 各バイナリから共有するAPI取得・アーカイブ処理
+pub mod archive;
+pub mod chart;
+pub mod cli;
+pub mod client;
+pub mod decimal;
+pub mod pipeline;
+
+// エンドポイントごとのレスポンス変換処理
+pub mod categories;
+pub mod coins;
+pub mod companies;
+pub mod contracts;
+pub mod derivatives;
+pub mod exchanges;
+pub mod global;
+pub mod nfts;
+pub mod onchain;
+pub mod platform;
+pub mod price;
+pub mod search;
+
+// CoinGecko APIのベースURL
+pub const COINGECKO_API: &str = "https://api.coingecko.com/api/v3";
+
+// DEX Screener APIのベースURL
+pub const DEXSCREENER_API: &str = "https://api.dexscreener.com";
+
+// タスク間で受け渡しできるエラー型
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
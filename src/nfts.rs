@@ -0,0 +1,459 @@
+// /nfts/list のレスポンスを nfts.collection_list に、
+// /nfts/{id} のレスポンスを nfts.collections に変換する
+use chrono::{DateTime, Utc};
+// BigDecimal → i64への変換のため
+use num_traits::ToPrimitive;
+// JSONデシリアライズ用
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+// PostgreSQL用の接続とBigDecimal型
+use sqlx::{PgConnection, types::BigDecimal};
+// market_chart の系列を時刻ごとにまとめるため
+use std::collections::BTreeMap;
+
+use crate::{archive::Snapshot, chart, client::Client, decimal, Error};
+
+// /nfts/list の order に指定できる値
+pub const ORDERS: [&str; 8] = [
+    "h24_volume_usd_desc",
+    "h24_volume_usd_asc",
+    "h24_volume_native_desc",
+    "h24_volume_native_asc",
+    "floor_price_native_desc",
+    "floor_price_native_asc",
+    "market_cap_usd_desc",
+    "market_cap_usd_asc",
+];
+
+// --contracts に指定するコントラクトの形式
+pub const CONTRACT_FORMAT: &str = "<asset_platform_id>:<contract_address>";
+
+// /nfts/list の1ページあたりの件数（APIの上限）
+pub const LIST_PER_PAGE: usize = 250;
+
+// NFT一覧取得用の構造体（/nfts/list の1件分）
+#[derive(Debug, Deserialize)]
+pub struct NftListItem {
+    pub id: String,
+    pub contract_address: Option<String>,
+    pub name: Option<String>,
+    pub asset_platform_id: Option<String>,
+    pub symbol: Option<String>,
+}
+
+// 各NFTの詳細情報の構造体（/nfts/{id} のレスポンス構造）
+#[derive(Debug, Deserialize)]
+pub struct NftDetail {
+    id: String,
+    name: Option<String>,
+    symbol: Option<String>,
+    contract_address: Option<String>,
+    asset_platform_id: Option<String>,
+    native_currency: Option<String>,
+    #[serde(default, deserialize_with = "native_usd")]
+    floor_price: NativeUsd,
+    #[serde(default, deserialize_with = "native_usd")]
+    volume_24h: NativeUsd,
+    #[serde(default, deserialize_with = "native_usd")]
+    market_cap: NativeUsd,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    number_of_unique_addresses: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    total_supply: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "native_usd")]
+    floor_price_24h_percentage_change: NativeUsd,
+    #[serde(default, deserialize_with = "native_usd")]
+    floor_price_7d_percentage_change: NativeUsd,
+    #[serde(default, deserialize_with = "native_usd")]
+    floor_price_30d_percentage_change: NativeUsd,
+}
+
+// ネイティブ通貨建てとUSD建ての組（floor_price・market_cap・変化率など）
+#[derive(Debug, Default, Deserialize)]
+struct NativeUsd {
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    native_currency: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "decimal::lenient")]
+    usd: Option<BigDecimal>,
+}
+
+// null の場合も欠けている場合と同じく空の組にする（#[serde(default)] は null を扱わない）
+fn native_usd<'de, D>(deserializer: D) -> Result<NativeUsd, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<NativeUsd>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+// /nfts/list の1ページを nfts.collection_list に反映し、ページの内容を順番通りに返す
+pub async fn store_list(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<Vec<NftListItem>, Error> {
+    let list: Vec<NftListItem> = snapshot.parse()?;
+
+    for item in &list {
+        sqlx::query!(
+            r#"
+            INSERT INTO nfts.collection_list (
+                id,
+                contract_address,
+                name,
+                asset_platform_id,
+                symbol,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO UPDATE
+            SET contract_address = EXCLUDED.contract_address,
+                name = EXCLUDED.name,
+                asset_platform_id = EXCLUDED.asset_platform_id,
+                symbol = EXCLUDED.symbol,
+                fetched_at = EXCLUDED.fetched_at
+            WHERE nfts.collection_list.fetched_at < EXCLUDED.fetched_at
+            "#,
+            item.id,
+            item.contract_address,
+            item.name,
+            item.asset_platform_id,
+            item.symbol,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(list)
+}
+
+// 巡回の位置（まだ巡回していなければ None）
+pub async fn load_cursor(conn: &mut PgConnection, name: &str) -> Result<Option<String>, Error> {
+    let last_id = sqlx::query_scalar!(
+        "SELECT last_id FROM nfts.refresh_cursor WHERE name = $1",
+        name
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(last_id.flatten())
+}
+
+// 巡回の位置を記録（None で先頭に戻す）
+pub async fn save_cursor(conn: &mut PgConnection, name: &str, last_id: Option<&str>) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO nfts.refresh_cursor (name, last_id, updated_at)
+        VALUES ($1, $2, now())
+        ON CONFLICT (name) DO UPDATE
+        SET last_id = EXCLUDED.last_id,
+            updated_at = EXCLUDED.updated_at
+        "#,
+        name,
+        last_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// /nfts/{id}/market_chart のレスポンス（各系列は [[timestamp, value], ...]）
+#[derive(Debug, Deserialize)]
+struct MarketChart {
+    #[serde(default)]
+    floor_price_usd: Value,
+    #[serde(default)]
+    floor_price_native: Value,
+    #[serde(default)]
+    h24_volume_usd: Value,
+    #[serde(default)]
+    h24_volume_native: Value,
+    #[serde(default)]
+    market_cap_usd: Value,
+    #[serde(default)]
+    market_cap_native: Value,
+}
+
+// market_chart の1時点分
+#[derive(Debug, Default)]
+struct ChartPoint {
+    floor_price_usd: Option<BigDecimal>,
+    floor_price_native: Option<BigDecimal>,
+    volume_24h_usd: Option<BigDecimal>,
+    volume_24h_native: Option<BigDecimal>,
+    market_cap_usd: Option<BigDecimal>,
+    market_cap_native: Option<BigDecimal>,
+}
+
+// 系列の値を入れる ChartPoint の項目
+type ChartField = fn(&mut ChartPoint) -> &mut Option<BigDecimal>;
+
+pub async fn insert_collection(
+    conn: &mut PgConnection,
+    detail: &NftDetail,
+    fetched_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    // デバッグ出力（取得したNFTの詳細）
+    println!("📥 Inserting NFT: {:?}", detail);
+
+    // ユニークアドレス数は整数で保存（小数で返ってきた場合は切り捨て）
+    let number_of_unique_addresses = detail.number_of_unique_addresses.as_ref()
+        .and_then(|n| n.to_i64());
+
+    // 取得した情報を nfts.collections テーブルにINSERT
+    sqlx::query!(
+        r#"
+        INSERT INTO nfts.collections (
+            id,
+            name,
+            floor_price,
+            volume_24h,
+            symbol,
+            contract_address,
+            asset_platform_id,
+            native_currency,
+            floor_price_native,
+            volume_24h_native,
+            market_cap_usd,
+            market_cap_native,
+            number_of_unique_addresses,
+            total_supply,
+            floor_price_change_24h_usd,
+            floor_price_change_24h_native,
+            floor_price_change_7d_usd,
+            floor_price_change_7d_native,
+            floor_price_change_30d_usd,
+            floor_price_change_30d_native,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+        "#,
+        detail.id,
+        detail.name,
+        detail.floor_price.usd,
+        detail.volume_24h.usd,
+        detail.symbol,
+        detail.contract_address,
+        detail.asset_platform_id,
+        detail.native_currency,
+        detail.floor_price.native_currency,
+        detail.volume_24h.native_currency,
+        detail.market_cap.usd,
+        detail.market_cap.native_currency,
+        number_of_unique_addresses,
+        detail.total_supply,
+        detail.floor_price_24h_percentage_change.usd,
+        detail.floor_price_24h_percentage_change.native_currency,
+        detail.floor_price_7d_percentage_change.usd,
+        detail.floor_price_7d_percentage_change.native_currency,
+        detail.floor_price_30d_percentage_change.usd,
+        detail.floor_price_30d_percentage_change.native_currency,
+        fetched_at
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// (プラットフォーム, コントラクト) からコレクションIDを引く
+// 解決済みの対応表を優先し、無ければ /nfts/list の一覧から探す
+pub async fn lookup_contract(
+    conn: &mut PgConnection,
+    asset_platform_id: &str,
+    contract_address: &str,
+) -> Result<Option<String>, Error> {
+    let cached = sqlx::query_scalar!(
+        r#"
+        SELECT collection_id
+        FROM nfts.contract_ids
+        WHERE asset_platform_id = $1 AND lower(contract_address) = lower($2)
+        "#,
+        asset_platform_id,
+        contract_address
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    if cached.is_some() {
+        return Ok(cached);
+    }
+
+    let listed = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM nfts.collection_list
+        WHERE asset_platform_id = $1 AND lower(contract_address) = lower($2)
+        ORDER BY fetched_at DESC
+        LIMIT 1
+        "#,
+        asset_platform_id,
+        contract_address
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(listed)
+}
+
+// /nfts/{asset_platform_id}/contract/{contract_address} を nfts.collections に挿入し、
+// 対応表を更新してコレクションIDを返す
+pub async fn store_contract(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<String, Error> {
+    let asset_platform_id = snapshot.param("asset_platform_id").ok_or("asset_platform_id is missing")?;
+    let contract_address = snapshot.param("contract_address").ok_or("contract_address is missing")?;
+    let detail: NftDetail = snapshot.parse()?;
+
+    insert_collection(conn, &detail, snapshot.fetched_at).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO nfts.contract_ids (
+            asset_platform_id,
+            contract_address,
+            collection_id,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (asset_platform_id, lower(contract_address)) DO UPDATE
+        SET collection_id = EXCLUDED.collection_id,
+            fetched_at = EXCLUDED.fetched_at
+        WHERE nfts.contract_ids.fetched_at < EXCLUDED.fetched_at
+        "#,
+        asset_platform_id,
+        contract_address,
+        detail.id,
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(detail.id)
+}
+
+// (プラットフォーム, コントラクト) をコレクションIDに解決する
+// 対応表・一覧に無ければコントラクトのエンドポイントを呼び、結果をキャッシュする
+pub async fn resolve_contract(
+    client: &Client,
+    conn: &mut PgConnection,
+    asset_platform_id: &str,
+    contract_address: &str,
+) -> Result<String, Error> {
+    if let Some(id) = lookup_contract(conn, asset_platform_id, contract_address).await? {
+        return Ok(id);
+    }
+
+    let snapshot = client
+        .get(
+            "/nfts/{asset_platform_id}/contract/{contract_address}",
+            &[("asset_platform_id", asset_platform_id), ("contract_address", contract_address)],
+        )
+        .await?;
+    store_contract(conn, &snapshot).await
+}
+
+// アーカイブ済みのレスポンスから nfts.collections の該当行を作り直す
+pub async fn rebuild_collection(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM nfts.collections WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    insert_collection(conn, &snapshot.parse()?, snapshot.fetched_at).await
+}
+
+// /nfts/{id}/market_chart の系列を時刻ごとにまとめて nfts.market_chart に反映し、点の数を返す
+// 同じ時刻の点は、より新しい（または同じ）取得時刻のときだけ上書きする
+pub async fn store_market_chart(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<usize, Error> {
+    let collection_id = snapshot.param("id").ok_or("id is missing")?;
+    let market_chart: MarketChart = snapshot.parse()?;
+
+    let mut points: BTreeMap<DateTime<Utc>, ChartPoint> = BTreeMap::new();
+    let series: [(&Value, ChartField); 6] = [
+        (&market_chart.floor_price_usd, |p| &mut p.floor_price_usd),
+        (&market_chart.floor_price_native, |p| &mut p.floor_price_native),
+        (&market_chart.h24_volume_usd, |p| &mut p.volume_24h_usd),
+        (&market_chart.h24_volume_native, |p| &mut p.volume_24h_native),
+        (&market_chart.market_cap_usd, |p| &mut p.market_cap_usd),
+        (&market_chart.market_cap_native, |p| &mut p.market_cap_native),
+    ];
+    for (values, field) in series {
+        for (timestamp, value) in chart::points(values) {
+            *field(points.entry(timestamp).or_default()) = value;
+        }
+    }
+
+    for (timestamp, point) in &points {
+        sqlx::query!(
+            r#"
+            INSERT INTO nfts.market_chart (
+                collection_id,
+                timestamp,
+                floor_price_usd,
+                floor_price_native,
+                volume_24h_usd,
+                volume_24h_native,
+                market_cap_usd,
+                market_cap_native,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (collection_id, timestamp) DO UPDATE
+            SET floor_price_usd = EXCLUDED.floor_price_usd,
+                floor_price_native = EXCLUDED.floor_price_native,
+                volume_24h_usd = EXCLUDED.volume_24h_usd,
+                volume_24h_native = EXCLUDED.volume_24h_native,
+                market_cap_usd = EXCLUDED.market_cap_usd,
+                market_cap_native = EXCLUDED.market_cap_native,
+                fetched_at = EXCLUDED.fetched_at
+            WHERE nfts.market_chart.fetched_at <= EXCLUDED.fetched_at
+            "#,
+            collection_id,
+            timestamp,
+            point.floor_price_usd,
+            point.floor_price_native,
+            point.volume_24h_usd,
+            point.volume_24h_native,
+            point.market_cap_usd,
+            point.market_cap_native,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(points.len())
+}
+
+pub async fn rebuild_contract(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM nfts.collections WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store_contract(conn, snapshot).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn detail_with_null_price_objects() {
+        let detail: NftDetail = serde_json::from_value(json!({
+            "id": "pudgy-penguins",
+            "floor_price": { "native_currency": 10.5, "usd": "35000.25" },
+            "market_cap": null,
+            "floor_price_7d_percentage_change": null
+        }))
+        .unwrap();
+
+        assert_eq!(detail.floor_price.native_currency, Some(BigDecimal::from_str("10.5").unwrap()));
+        assert_eq!(detail.floor_price.usd, Some(BigDecimal::from_str("35000.25").unwrap()));
+        assert_eq!(detail.market_cap.usd, None);
+        assert_eq!(detail.floor_price_7d_percentage_change.native_currency, None);
+        assert_eq!(detail.volume_24h.usd, None);
+    }
+}
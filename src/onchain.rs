@@ -0,0 +1,269 @@
+// DEX Screener のペア情報を onchain.dex_token_prices と onchain.pair_activity に変換する
+use serde_json::Value;
+use std::collections::HashSet;
+use sqlx::PgConnection;
+
+use crate::{archive::Snapshot, decimal, Error};
+
+// --pairs・ONCHAIN_PAIRS に指定するペアの形式
+pub const PAIR_FORMAT: &str = "<chain>:<pair_address>";
+
+// /latest/dex/pairs/{chain}/{pair} に1回で指定できるペアの数
+pub const PAIRS_PER_REQUEST: usize = 30;
+
+// 出来高・取引回数・価格変化率の期間
+const WINDOWS: [&str; 4] = ["m5", "h1", "h6", "h24"];
+
+// 1回の取り込みの検証結果
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub stored: usize,
+    pub rejected: usize,
+    pub missing_price: usize,
+    pub missing_liquidity: usize,
+}
+
+impl ValidationReport {
+    pub fn merge(&mut self, other: &ValidationReport) {
+        self.stored += other.stored;
+        self.rejected += other.rejected;
+        self.missing_price += other.missing_price;
+        self.missing_liquidity += other.missing_liquidity;
+    }
+}
+
+// 追跡するペアを (チェーン, ペアアドレス) で返す
+pub async fn load_watchlist(conn: &mut PgConnection) -> Result<Vec<(String, String)>, Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT chain, pair_address
+        FROM onchain.watchlist
+        WHERE active
+        ORDER BY chain, pair_address
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| (row.chain, row.pair_address)).collect())
+}
+
+// 取り込めないペアを理由とともに記録
+async fn reject_pair(
+    conn: &mut PgConnection,
+    chain: Option<&str>,
+    pair_address: Option<&str>,
+    reason: &str,
+    payload: Option<&Value>,
+    snapshot: &Snapshot,
+) -> Result<(), Error> {
+    println!("⚠️ Rejected DEX pair {}:{}: {}", chain.unwrap_or("?"), pair_address.unwrap_or("?"), reason);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO onchain.rejected_pairs (
+            chain,
+            pair_address,
+            reason,
+            payload,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        chain,
+        pair_address,
+        reason,
+        payload,
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// ペアを識別できない場合の理由（取り込めるなら None）
+fn reject_reason(pair: &Value) -> Option<&'static str> {
+    if !pair.is_object() {
+        return Some("pair is missing from the response");
+    }
+    if pair["dexId"].as_str().is_none_or(str::is_empty) {
+        return Some("dexId is missing");
+    }
+    if pair["baseToken"]["address"].as_str().is_none_or(str::is_empty) {
+        return Some("baseToken.address is missing");
+    }
+    None
+}
+
+// ペア1件分を挿入（価格・流動性が欠けている・解析できない場合はNULL）
+async fn insert_pair(
+    conn: &mut PgConnection,
+    pair: &Value,
+    snapshot: &Snapshot,
+    report: &mut ValidationReport,
+) -> Result<(), Error> {
+    let chain = pair["chainId"].as_str().or(snapshot.param("chain"));
+    let pair_address = pair["pairAddress"].as_str();
+
+    if let Some(reason) = reject_reason(pair) {
+        reject_pair(conn, chain, pair_address, reason, Some(pair), snapshot).await?;
+        report.rejected += 1;
+        return Ok(());
+    }
+
+    // データ抽出（priceUsd は文字列、liquidity.usd は数値で返る）
+    let exchange = pair["dexId"].as_str();
+    let token_address = pair["baseToken"]["address"].as_str();
+    let price_bd = decimal::from_value(&pair["priceUsd"]);
+    let liquidity_bd = decimal::from_value(&pair["liquidity"]["usd"]);
+    if price_bd.is_none() {
+        report.missing_price += 1;
+    }
+    if liquidity_bd.is_none() {
+        report.missing_liquidity += 1;
+    }
+
+    // 追加項目（無ければNULL）
+    let base_token_symbol = pair["baseToken"]["symbol"].as_str();
+    let quote_token_address = pair["quoteToken"]["address"].as_str();
+    let quote_token_symbol = pair["quoteToken"]["symbol"].as_str();
+    let price_native = decimal::from_value(&pair["priceNative"]);
+
+    // データ挿入
+    sqlx::query!(
+        r#"
+        INSERT INTO onchain.dex_token_prices (
+            exchange,
+            token_address,
+            price,
+            liquidity_usd,
+            chain,
+            pair_address,
+            base_token_symbol,
+            quote_token_address,
+            quote_token_symbol,
+            price_native,
+            fetched_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#,
+        exchange,
+        token_address,
+        price_bd,
+        liquidity_bd,
+        chain,
+        pair_address,
+        base_token_symbol,
+        quote_token_address,
+        quote_token_symbol,
+        price_native,
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+    report.stored += 1;
+
+    // 期間ごとの出来高・取引回数（チェーンとペアアドレスが分かる場合のみ）
+    let (Some(chain), Some(pair_address)) = (chain, pair_address) else {
+        return Ok(());
+    };
+    for window in WINDOWS {
+        let volume = &pair["volume"][window];
+        let txns = &pair["txns"][window];
+        let price_change = &pair["priceChange"][window];
+
+        // レスポンスにその期間の値が1つも無ければ行を作らない
+        if volume.is_null() && txns.is_null() && price_change.is_null() {
+            continue;
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO onchain.pair_activity (
+                chain,
+                pair_address,
+                window_name,
+                volume_usd,
+                buys,
+                sells,
+                price_change,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT DO NOTHING
+            "#,
+            chain,
+            pair_address,
+            window,
+            decimal::from_value(volume),
+            txns["buys"].as_i64(),
+            txns["sells"].as_i64(),
+            decimal::from_value(price_change),
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// レスポンスに含まれる全ペアを挿入し、検証結果を返す
+// （複数指定時は pairs、単一ペアの古いアーカイブは pair に入っている）
+// 指定したのにレスポンスに含まれなかったペアも理由付きで記録する
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<ValidationReport, Error> {
+    let mut report = ValidationReport::default();
+    let pairs: Vec<&Value> = match &snapshot.body["pairs"] {
+        Value::Array(pairs) => pairs.iter().collect(),
+        _ if snapshot.body["pair"].is_object() => vec![&snapshot.body["pair"]],
+        _ => Vec::new(),
+    };
+
+    for pair in &pairs {
+        insert_pair(conn, pair, snapshot, &mut report).await?;
+    }
+
+    let returned: HashSet<String> = pairs
+        .iter()
+        .filter_map(|pair| pair["pairAddress"].as_str())
+        .map(str::to_lowercase)
+        .collect();
+    let requested = snapshot.param("pair").unwrap_or_default().split(',').filter(|p| !p.is_empty());
+    for pair_address in requested {
+        if !returned.contains(&pair_address.to_lowercase()) {
+            let chain = snapshot.param("chain");
+            reject_pair(conn, chain, Some(pair_address), "pair was not returned", None, snapshot).await?;
+            report.rejected += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM onchain.dex_token_prices WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM onchain.pair_activity WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM onchain.rejected_pairs WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await?;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+// 詳細APIを並列に取得し、結果を順次受け取るためのパイプライン
+use std::env;
+use tokio::sync::{mpsc, Semaphore};
+use std::sync::Arc;
+
+use crate::archive::Snapshot;
+use crate::client::Client;
+use crate::Error;
+
+// 同時に実行するリクエスト数の既定値
+const DEFAULT_WORKERS: usize = 4;
+
+// DBへ1トランザクションでまとめて書き込む件数
+pub const BATCH_SIZE: usize = 20;
+
+// 同時リクエスト数（FETCH_WORKERS で変更できる）
+pub fn workers() -> usize {
+    env::var("FETCH_WORKERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_WORKERS)
+}
+
+// endpoint の {id} を ids の各値で置き換えて並列に取得する（params は全件共通）
+// 取得できた順に (id, 結果) が届き、失敗も1件ずつ受け取れる
+// リクエスト間隔はクライアントのレート制限に従う
+pub fn fetch_each(
+    client: &Client,
+    endpoint: &'static str,
+    params: &'static [(&'static str, &'static str)],
+    ids: Vec<String>,
+) -> mpsc::Receiver<(String, Result<Snapshot, Error>)> {
+    let workers = workers();
+    let (tx, rx) = mpsc::channel(workers * 2);
+    let semaphore = Arc::new(Semaphore::new(workers));
+    let client = client.clone();
+
+    tokio::spawn(async move {
+        for id in ids {
+            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                break;
+            };
+            let client = client.clone();
+            let tx = tx.clone();
+
+            tokio::spawn(async move {
+                let mut request = vec![("id", id.as_str())];
+                request.extend_from_slice(params);
+                let result = client.get(endpoint, &request).await;
+                drop(permit);
+                let _ = tx.send((id, result)).await;
+            });
+        }
+    });
+
+    rx
+}
@@ -0,0 +1,56 @@
+// /asset_platforms のレスポンスを asset_platforms.platforms に変換する
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+use crate::{archive::Snapshot, Error};
+
+#[derive(Debug, Deserialize)]
+pub struct Platform {
+    id: String,
+    name: Option<String>,
+    chain_identifier: Option<i32>,
+    shortname: Option<String>,
+}
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let platforms: Vec<Platform> = snapshot.parse()?;
+
+    // 各プラットフォーム情報をDBに挿入
+    for platform in platforms {
+        println!("📥 Inserting Platform: {:?}", platform.id);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO asset_platforms.platforms (
+                id,
+                name,
+                chain_identifier,
+                shortname,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            platform.id,
+            platform.name,
+            platform.chain_identifier,
+            platform.shortname,
+            snapshot.fetched_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM asset_platforms.platforms WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await
+}
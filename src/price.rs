@@ -0,0 +1,58 @@
+// /simple/price のレスポンスを simple.current_price に変換する
+use num_traits::FromPrimitive;
+use sqlx::{PgConnection, types::BigDecimal};
+use std::collections::HashMap;
+
+use crate::{archive::Snapshot, Error};
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    // リクエスト時のコインと通貨（"bitcoin,ethereum,ripple" / "usd,jpy"）
+    let coin_ids: Vec<&str> = snapshot.param("ids").unwrap_or_default().split(',').collect();
+    let currencies: Vec<&str> = snapshot.param("vs_currencies").unwrap_or_default().split(',').collect();
+    let resp: HashMap<String, HashMap<String, f64>> = snapshot.parse()?;
+
+    // コインごとにループ処理
+    for &coin in &coin_ids {
+        if let Some(data) = resp.get(coin) {
+            for &currency in &currencies {
+                let price = data.get(currency).and_then(|v| BigDecimal::from_f64(*v));
+                let market_cap = data.get(&format!("{}_market_cap", currency)).and_then(|v| BigDecimal::from_f64(*v));
+                let volume_24h = data.get(&format!("{}_24h_vol", currency)).and_then(|v| BigDecimal::from_f64(*v));
+                let change_24h = data.get(&format!("{}_24h_change", currency)).and_then(|v| BigDecimal::from_f64(*v));
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO simple.current_price 
+                    (id, vs_currency, price, market_cap, volume_24h, change_24h, fetched_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    "#,
+                    coin,
+                    currency,
+                    price,
+                    market_cap,
+                    volume_24h,
+                    change_24h,
+                    snapshot.fetched_at
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+        } else {
+            println!("⚠️ No data found for {}", coin);
+        }
+    }
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM simple.current_price WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await
+}
@@ -0,0 +1,71 @@
+// /search/trending のレスポンスを search.trending_coins に変換する
+use serde::Deserialize;
+use sqlx::PgConnection;
+
+use crate::{archive::Snapshot, Error};
+
+#[derive(Debug, Deserialize)]
+pub struct TrendingResponse {
+    coins: Vec<TrendingCoinWrapper>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendingCoinWrapper {
+    item: TrendingCoin,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendingCoin {
+    id: String,
+    name: Option<String>,
+    symbol: Option<String>,
+    market_cap_rank: Option<i32>,
+    score: Option<i32>,
+}
+
+pub async fn store(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    let response: TrendingResponse = snapshot.parse()?;
+
+    // 各コイン情報をDBに挿入
+    for coin in response.coins {
+        let c = coin.item;
+
+        println!("📥 Inserting Trending Coin: {:?}", c);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO search.trending_coins (
+                id,
+                name,
+                symbol,
+                market_cap_rank,
+                score,
+                fetched_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            c.id,
+            c.name,
+            c.symbol,
+            c.market_cap_rank,
+            c.score,
+            snapshot.fetched_at,
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+// アーカイブ済みのレスポンスから該当スナップショットの行を作り直す
+pub async fn rebuild(conn: &mut PgConnection, snapshot: &Snapshot) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM search.trending_coins WHERE fetched_at = $1",
+        snapshot.fetched_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    store(conn, snapshot).await
+}